@@ -0,0 +1,276 @@
+use core::fmt;
+use core::mem::MaybeUninit;
+use core::ptr;
+use core::slice;
+
+use super::{sift_down, sift_up, Compare, HeapError, HeapProperty};
+
+/// A binary heap that stores up to `N` elements inline, without allocating.
+/// Like a bounded `Heap`, it reports a full heap when `add` runs out of room.
+pub struct ArrayHeap<T, const N: usize, C = HeapProperty> {
+    elements: [MaybeUninit<T>; N],
+    size: usize,
+    comparator: C,
+}
+
+impl<T: Ord, const N: usize> ArrayHeap<T, N> {
+    /// Creates an empty min heap.
+    pub fn new() -> Self {
+        ArrayHeap::with_comparator(HeapProperty::Min)
+    }
+
+    pub fn new_as(property: HeapProperty) -> Self {
+        ArrayHeap::with_comparator(property)
+    }
+}
+
+impl<T, const N: usize, C> ArrayHeap<T, N, C> {
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.size == N
+    }
+
+    pub fn clear(&mut self) {
+        let elements: *mut [T] = self.as_mut_slice();
+        self.size = 0;
+
+        // SAFETY: the first `size` elements were initialized, and resetting
+        // `size` beforehand keeps them from being dropped again if a `drop`
+        // panics.
+        unsafe { ptr::drop_in_place(elements) };
+    }
+
+    /// Returns an iterator over the elements in the heap in arbitrary order.
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.as_slice().iter()
+    }
+
+    fn as_slice(&self) -> &[T] {
+        // SAFETY: the first `size` elements are initialized.
+        unsafe { slice::from_raw_parts(self.elements.as_ptr().cast::<T>(), self.size) }
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        Self::slice_mut(&mut self.elements, self.size)
+    }
+
+    /// Borrows the initialized elements apart from the comparator, so both
+    /// can be passed to the sifting functions.
+    fn slice_mut(elements: &mut [MaybeUninit<T>; N], size: usize) -> &mut [T] {
+        // SAFETY: callers pass the heap's `size`, and the first `size`
+        // elements are initialized.
+        unsafe { slice::from_raw_parts_mut(elements.as_mut_ptr().cast::<T>(), size) }
+    }
+}
+
+impl<T, const N: usize, C: Compare<T>> ArrayHeap<T, N, C> {
+    pub fn with_comparator(comparator: C) -> Self {
+        ArrayHeap {
+            elements: [const { MaybeUninit::uninit() }; N],
+            size: 0,
+            comparator,
+        }
+    }
+
+    pub fn peek(&self) -> Result<&T, HeapError> {
+        self.validate_non_empty_heap()?;
+
+        Ok(&self.as_slice()[0])
+    }
+
+    pub fn pop(&mut self) -> Result<T, HeapError> {
+        self.validate_non_empty_heap()?;
+
+        let last_index = self.size - 1;
+        self.as_mut_slice().swap(0, last_index);
+        self.size = last_index;
+
+        // SAFETY: the slot right after the remaining elements was
+        // initialized, and it is no longer counted by `size`.
+        let result = unsafe { self.elements[self.size].assume_init_read() };
+
+        let comparator = &self.comparator;
+        sift_down(
+            Self::slice_mut(&mut self.elements, self.size),
+            0,
+            comparator,
+        );
+
+        Ok(result)
+    }
+
+    pub fn add(&mut self, element: T) -> Result<(), HeapError> {
+        if self.is_full() {
+            return Err(HeapError::Full { capacity: N });
+        }
+
+        self.elements[self.size].write(element);
+        self.size += 1;
+
+        let index = self.size - 1;
+        let comparator = &self.comparator;
+        sift_up(
+            Self::slice_mut(&mut self.elements, self.size),
+            index,
+            comparator,
+        );
+
+        Ok(())
+    }
+
+    fn validate_non_empty_heap(&self) -> Result<(), HeapError> {
+        if self.is_empty() {
+            return Err(HeapError::Empty);
+        }
+
+        Ok(())
+    }
+}
+
+impl<T, const N: usize, C> Drop for ArrayHeap<T, N, C> {
+    fn drop(&mut self) {
+        // SAFETY: the first `size` elements are initialized and are not used
+        // after this.
+        unsafe { ptr::drop_in_place(self.as_mut_slice()) };
+    }
+}
+
+impl<T: Ord, const N: usize> Default for ArrayHeap<T, N> {
+    fn default() -> Self {
+        ArrayHeap::new()
+    }
+}
+
+impl<T: fmt::Debug, const N: usize, C> fmt::Debug for ArrayHeap<T, N, C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod array_heap_tests {
+    use super::ArrayHeap;
+    use crate::{HeapError, HeapProperty};
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[test]
+    fn peek_and_pop_on_empty_heap() {
+        let mut heap: ArrayHeap<i32, 3> = ArrayHeap::new();
+
+        assert_eq!(HeapError::Empty, heap.peek().unwrap_err());
+        assert_eq!(HeapError::Empty, heap.pop().unwrap_err());
+    }
+
+    #[test]
+    fn add_to_full_heap() {
+        let mut heap: ArrayHeap<i32, 2> = ArrayHeap::default();
+
+        heap.add(2).unwrap();
+        heap.add(1).unwrap();
+
+        assert!(heap.is_full());
+        assert_eq!(HeapError::Full { capacity: 2 }, heap.add(3).unwrap_err());
+    }
+
+    #[test]
+    fn zero_capacity_heap() {
+        let mut heap: ArrayHeap<i32, 0> = ArrayHeap::new();
+
+        assert!(heap.is_empty());
+        assert!(heap.is_full());
+        assert_eq!(HeapError::Full { capacity: 0 }, heap.add(1).unwrap_err());
+    }
+
+    #[test]
+    fn add_and_pop_as_max() {
+        let mut heap: ArrayHeap<i32, 5> = ArrayHeap::new_as(HeapProperty::Max);
+        for element in [3, 5, 1, 4, 2] {
+            heap.add(element).unwrap();
+        }
+
+        assert_eq!(5, heap.len());
+        assert_eq!(Ok(&5), heap.peek());
+
+        let mut popped = Vec::new();
+        while let Ok(element) = heap.pop() {
+            popped.push(element);
+        }
+
+        assert_eq!(vec![5, 4, 3, 2, 1], popped);
+    }
+
+    #[test]
+    fn add_and_pop_with_comparator() {
+        let mut heap: ArrayHeap<&str, 4, _> =
+            ArrayHeap::with_comparator(|a: &&str, b: &&str| a.len().cmp(&b.len()));
+        for element in ["three", "one", "four"] {
+            heap.add(element).unwrap();
+        }
+
+        assert_eq!(4, heap.capacity());
+        assert_eq!(Ok("one"), heap.pop());
+        assert_eq!(Ok("four"), heap.pop());
+        assert_eq!(Ok("three"), heap.pop());
+    }
+
+    #[test]
+    fn debug_lists_elements() {
+        let mut heap: ArrayHeap<i32, 4> = ArrayHeap::new();
+        for element in [2, 1, 3] {
+            heap.add(element).unwrap();
+        }
+
+        assert_eq!("[1, 2, 3]", format!("{:?}", heap));
+    }
+
+    #[test]
+    fn debug_does_not_need_comparator() {
+        fn debug<T: std::fmt::Debug, const N: usize, C>(heap: &ArrayHeap<T, N, C>) -> String {
+            format!("{:?}", heap)
+        }
+        let heap: ArrayHeap<i32, 2> = ArrayHeap::new();
+
+        assert_eq!("[]", debug(&heap));
+    }
+
+    #[test]
+    fn drops_remaining_elements() {
+        let drops = Rc::new(Cell::new(0));
+        struct Counted(i32, Rc<Cell<usize>>);
+        impl Drop for Counted {
+            fn drop(&mut self) {
+                self.1.set(self.1.get() + 1);
+            }
+        }
+        let mut heap: ArrayHeap<Counted, 4, _> =
+            ArrayHeap::with_comparator(|a: &Counted, b: &Counted| a.0.cmp(&b.0));
+        for element in [3, 1, 2, 4] {
+            heap.add(Counted(element, drops.clone())).unwrap();
+        }
+
+        assert_eq!(1, heap.pop().unwrap().0);
+        assert_eq!(1, drops.get());
+
+        heap.clear();
+
+        assert_eq!(4, drops.get());
+
+        heap.add(Counted(5, drops.clone())).unwrap();
+        heap.add(Counted(6, drops.clone())).unwrap();
+        drop(heap);
+
+        assert_eq!(6, drops.get());
+    }
+}
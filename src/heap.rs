@@ -0,0 +1,2602 @@
+use alloc::vec::{self, Vec};
+use core::cmp::Ordering;
+use core::fmt;
+use core::iter::FromIterator;
+use core::mem;
+use core::ops::{Deref, DerefMut};
+use core::slice;
+
+use super::{
+    d_ary_find_violation, d_ary_heapify, d_ary_sift_down, d_ary_sift_up, get_first_child_index,
+    precedes, ByKey, Compare, HeapError, HeapProperty, MaxOrder, MinOrder,
+};
+
+/// A heap is either bounded, holding at most the `size` elements it was
+/// created with, or growable, reallocating its storage as elements are added.
+///
+/// Every node has up to `D` children, two by default. A shallower tree with
+/// more children per node speeds up `add` at the cost of more comparisons in
+/// `pop`.
+///
+/// # Panic safety
+///
+/// If the comparator or an `Ord` impl panics partway through an operation,
+/// every element is still owned exactly once: none is duplicated, leaked or
+/// dropped twice. The elements may no longer be in heap order, which
+/// `rebuild` restores, but the heap can still be used and dropped. An element
+/// on its way out of the heap, such as the one `pop` was about to return, is
+/// dropped during unwinding.
+#[derive(Clone)]
+pub struct Heap<T, C = HeapProperty, const D: usize = 2> {
+    elements: Vec<T>,
+    bound: Option<usize>,
+    comparator: C,
+}
+
+/// A binary heap with the smallest element on top, fixed at compile time.
+/// Other arities are spelled out as `Heap<T, MinOrder, D>`.
+pub type MinHeap<T> = Heap<T, MinOrder>;
+
+/// A binary heap with the largest element on top, fixed at compile time.
+/// Other arities are spelled out as `Heap<T, MaxOrder, D>`.
+pub type MaxHeap<T> = Heap<T, MaxOrder>;
+
+impl<T: Ord> Heap<T> {
+    pub fn new(size: usize) -> Self {
+        Heap::with_comparator(size, HeapProperty::Min)
+    }
+
+    pub fn new_as(size: usize, property: HeapProperty) -> Self {
+        Heap::with_comparator(size, property)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Heap::with_capacity_and_comparator(capacity, HeapProperty::Min)
+    }
+
+    pub fn with_capacity_as(capacity: usize, property: HeapProperty) -> Self {
+        Heap::with_capacity_and_comparator(capacity, property)
+    }
+
+    /// Builds a growable heap out of `elements` in O(n) time.
+    pub fn from_vec(elements: Vec<T>, property: HeapProperty) -> Self {
+        Heap::from_vec_with_comparator(elements, property)
+    }
+}
+
+impl<T, F> Heap<T, ByKey<F>> {
+    /// Creates a growable heap ordered by the key `key` extracts from each
+    /// element.
+    pub fn by_key<K: Ord>(key: F, property: HeapProperty) -> Self
+    where
+        F: Fn(&T) -> K,
+    {
+        Heap::with_capacity_and_comparator(0, ByKey::new(key, property))
+    }
+
+    pub fn by_key_bounded<K: Ord>(size: usize, key: F, property: HeapProperty) -> Self
+    where
+        F: Fn(&T) -> K,
+    {
+        Heap::with_comparator(size, ByKey::new(key, property))
+    }
+}
+
+impl<T, C: Compare<T>> Heap<T, C> {
+    pub fn with_comparator(size: usize, comparator: C) -> Self {
+        Heap::bounded_with_comparator(size, comparator)
+    }
+
+    pub fn with_capacity_and_comparator(capacity: usize, comparator: C) -> Self {
+        Heap::growable_with_comparator(capacity, comparator)
+    }
+
+    /// Builds a growable heap out of `elements` in O(n) time.
+    pub fn from_vec_with_comparator(elements: Vec<T>, comparator: C) -> Self {
+        Heap::growable_from_vec(elements, comparator)
+    }
+}
+
+/// Constructors for heaps whose comparator is fully described by its type,
+/// such as `MinHeap` and `MaxHeap`.
+impl<T, O: Compare<T> + Default, const D: usize> Heap<T, O, D> {
+    pub fn bounded(size: usize) -> Self {
+        Heap::bounded_with_comparator(size, O::default())
+    }
+
+    pub fn growable(capacity: usize) -> Self {
+        Heap::growable_with_comparator(capacity, O::default())
+    }
+}
+
+/// The `new` and `with_capacity` constructors of heaps whose comparator is
+/// fully described by their type, as in `MinHeap::new(size)`. They live in a
+/// trait because inherent ones would make a bare `Heap::new` ambiguous.
+pub trait TypedHeap: Sized {
+    /// Creates an empty bounded heap.
+    fn new(size: usize) -> Self;
+
+    /// Creates an empty growable heap.
+    fn with_capacity(capacity: usize) -> Self;
+}
+
+impl<T, O: Compare<T> + Default, const D: usize> TypedHeap for Heap<T, O, D> {
+    fn new(size: usize) -> Self {
+        Heap::bounded(size)
+    }
+
+    fn with_capacity(capacity: usize) -> Self {
+        Heap::growable(capacity)
+    }
+}
+
+impl<T, C: Compare<T>, const D: usize> Heap<T, C, D> {
+    const ARITY_CHECK: () = assert!(D >= 2, "Heap arity must be at least 2.");
+
+    /// Creates a bounded heap of any arity, which is taken from its type, as
+    /// in `Heap::<u32, HeapProperty, 4>::bounded_with_comparator(size, cmp)`.
+    pub fn bounded_with_comparator(size: usize, comparator: C) -> Self {
+        Heap::from_parts(Vec::with_capacity(size), Some(size), comparator)
+    }
+
+    /// Creates a growable heap of any arity, which is taken from its type.
+    pub fn growable_with_comparator(capacity: usize, comparator: C) -> Self {
+        Heap::from_parts(Vec::with_capacity(capacity), None, comparator)
+    }
+
+    /// Builds a growable heap of any arity, which is taken from its type, out
+    /// of `elements` in O(n) time.
+    pub fn growable_from_vec(elements: Vec<T>, comparator: C) -> Self {
+        let mut heap = Heap::from_parts(elements, None, comparator);
+        heap.rebuild();
+        heap
+    }
+
+    fn from_parts(elements: Vec<T>, bound: Option<usize>, comparator: C) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::ARITY_CHECK;
+
+        Heap {
+            elements,
+            bound,
+            comparator,
+        }
+    }
+
+    pub fn is_bounded(&self) -> bool {
+        self.bound.is_some()
+    }
+
+    /// Returns the fixed size of a bounded heap, or the number of elements a
+    /// growable heap can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        match self.bound {
+            Some(size) => size,
+            None => self.elements.capacity(),
+        }
+    }
+
+    /// Reserves room for at least `additional` more elements in a growable
+    /// heap. Bounded heaps allocate their whole size up front, so this has no
+    /// effect on them.
+    pub fn reserve(&mut self, additional: usize) {
+        if self.bound.is_none() {
+            self.elements.reserve(additional);
+        }
+    }
+
+    /// Releases the unused storage of a growable heap. Bounded heaps keep
+    /// their whole size allocated, so this has no effect on them.
+    pub fn shrink_to_fit(&mut self) {
+        if self.bound.is_none() {
+            self.elements.shrink_to_fit();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Returns `true` if the heap is bounded and `add` would fail with
+    /// `HeapError::Full`. Growable heaps are never full.
+    pub fn is_full(&self) -> bool {
+        Some(self.elements.len()) == self.bound
+    }
+
+    /// Removes all elements, keeping the allocated storage.
+    pub fn clear(&mut self) {
+        self.elements.clear();
+    }
+
+    /// Returns an iterator over the elements in the heap in arbitrary order.
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.elements.iter()
+    }
+
+    /// Returns an iterator over the elements in the order `pop` would yield
+    /// them, without modifying the heap. Yielding `k` elements costs
+    /// O(k log k).
+    pub fn iter_sorted(&self) -> IterSorted<'_, T, C, D> {
+        let mut frontier = Heap::with_capacity_and_comparator(
+            0,
+            IndexOrder {
+                elements: &self.elements,
+                comparator: &self.comparator,
+            },
+        );
+        if !self.elements.is_empty() {
+            frontier.elements.push(0);
+        }
+
+        IterSorted { frontier }
+    }
+
+    /// Removes all elements from the heap, yielding them in arbitrary order.
+    pub fn drain(&mut self) -> vec::Drain<'_, T> {
+        self.elements.drain(..)
+    }
+
+    /// Removes all elements from the heap, yielding them lazily in the order
+    /// `pop` would. Elements left when the iterator is dropped are removed
+    /// too.
+    pub fn drain_sorted(&mut self) -> DrainSorted<'_, T, C, D> {
+        DrainSorted { heap: self }
+    }
+
+    pub fn peek(&self) -> Result<&T, HeapError> {
+        self.validate_non_empty_heap()?;
+
+        Ok(&self.elements[0])
+    }
+
+    /// Returns a guard giving mutable access to the top element. The heap is
+    /// restored when the guard is dropped, if the element was modified.
+    pub fn peek_mut(&mut self) -> Result<PeekMut<'_, T, C, D>, HeapError> {
+        self.validate_non_empty_heap()?;
+
+        Ok(PeekMut {
+            heap: self,
+            sift: false,
+        })
+    }
+
+    pub fn pop(&mut self) -> Result<T, HeapError> {
+        self.validate_non_empty_heap()?;
+
+        let result = self.elements.swap_remove(0);
+
+        self.recalculate_down();
+        self.debug_check_invariant();
+
+        Ok(result)
+    }
+
+    pub fn add(&mut self, element: T) -> Result<(), HeapError> {
+        if let Some(capacity) = self.bound {
+            if self.elements.len() == capacity {
+                return Err(HeapError::Full { capacity });
+            }
+        }
+
+        self.elements.push(element);
+
+        self.recalculate_up();
+        self.debug_check_invariant();
+
+        Ok(())
+    }
+
+    /// Adds `element` and then pops the top of the heap with a single sift.
+    /// If `element` would come out first, it is returned right away and the
+    /// heap is left untouched. Works on a full bounded heap as well.
+    pub fn push_pop(&mut self, element: T) -> T {
+        if self.elements.is_empty() || !precedes(&self.comparator, &self.elements[0], &element) {
+            return element;
+        }
+
+        let result = mem::replace(&mut self.elements[0], element);
+
+        self.recalculate_down();
+
+        result
+    }
+
+    /// Pops the top of the heap and then adds `element` with a single sift.
+    /// Works on a full bounded heap as well.
+    pub fn replace(&mut self, element: T) -> Result<T, HeapError> {
+        self.validate_non_empty_heap()?;
+
+        let result = mem::replace(&mut self.elements[0], element);
+
+        self.recalculate_down();
+
+        Ok(result)
+    }
+
+    /// Removes the elements matching `predicate` and returns them in arbitrary
+    /// order. The heap is rebuilt once, in O(n) time, after the removal.
+    pub fn remove_where<F: FnMut(&T) -> bool>(&mut self, mut predicate: F) -> Vec<T> {
+        let mut removed = Vec::new();
+        let mut index = 0;
+        while index < self.elements.len() {
+            if predicate(&self.elements[index]) {
+                removed.push(self.elements.swap_remove(index));
+            } else {
+                index += 1;
+            }
+        }
+
+        if !removed.is_empty() {
+            self.rebuild();
+        }
+
+        removed
+    }
+
+    /// Keeps only the elements matching `predicate`. The heap is rebuilt once,
+    /// in O(n) time, after the removal.
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, predicate: F) {
+        let size = self.elements.len();
+        self.elements.retain(predicate);
+
+        if self.elements.len() != size {
+            self.rebuild();
+        }
+    }
+
+    /// Moves all elements of `other` into this heap, leaving `other` empty.
+    /// Depending on the sizes, the elements are either added one by one or
+    /// the whole heap is rebuilt. Nothing is moved if the heaps order in
+    /// different directions or if this heap is bounded and lacks room.
+    pub fn append(&mut self, other: &mut Self) -> Result<(), HeapError> {
+        if self.comparator.property() != other.comparator.property() {
+            return Err(HeapError::PropertyMismatch);
+        }
+
+        if let Some(capacity) = self.bound {
+            if self.elements.len() + other.elements.len() > capacity {
+                return Err(HeapError::Full { capacity });
+            }
+        }
+
+        let size = self.elements.len();
+        let rebuild = better_to_rebuild(size, other.elements.len());
+        self.elements.append(&mut other.elements);
+
+        if rebuild {
+            self.rebuild();
+        } else {
+            for index in size..self.elements.len() {
+                d_ary_sift_up::<_, _, D>(&mut self.elements[..=index], index, &self.comparator);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Melds two heaps into one, which keeps the bound of `heap1`. If the
+    /// heaps can't be melded, the error comes back together with both heaps,
+    /// untouched.
+    #[allow(clippy::type_complexity)]
+    pub fn merge(mut heap1: Self, mut heap2: Self) -> Result<Self, (HeapError, Self, Self)> {
+        match heap1.append(&mut heap2) {
+            Ok(()) => Ok(heap1),
+            Err(error) => Err((error, heap1, heap2)),
+        }
+    }
+
+    /// Consumes the heap and returns its elements in the order `pop` would
+    /// yield them: ascending for `Min` and descending for `Max`.
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        for end in (1..self.elements.len()).rev() {
+            self.elements.swap(0, end);
+            d_ary_sift_down::<_, _, D>(&mut self.elements[..end], 0, &self.comparator);
+        }
+        self.elements.reverse();
+
+        self.elements
+    }
+
+    /// Checks that every element is in heap order with its parent. Returns
+    /// the indices of the first parent and child pair out of order, which can
+    /// happen when a comparator is inconsistent or elements change their
+    /// order through interior mutability, or `None` if there is none.
+    pub fn check_invariant(&self) -> Option<(usize, usize)> {
+        d_ary_find_violation::<_, _, D>(&self.elements, &self.comparator)
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.check_invariant().is_none()
+    }
+
+    /// Restores heap order over all elements in O(n) time.
+    pub fn rebuild(&mut self) {
+        d_ary_heapify::<_, _, D>(&mut self.elements, &self.comparator);
+    }
+
+    fn validate_non_empty_heap(&self) -> Result<(), HeapError> {
+        if self.elements.is_empty() {
+            return Err(HeapError::Empty);
+        }
+
+        Ok(())
+    }
+
+    /// With the `check-invariants` feature, asserts the heap order in builds
+    /// with debug assertions. This costs O(n) time per call, so release
+    /// builds skip the check altogether.
+    fn debug_check_invariant(&self) {
+        if cfg!(all(debug_assertions, feature = "check-invariants")) {
+            if let Some((parent, child)) = self.check_invariant() {
+                panic!(
+                    "Violated heap property between parent {} and child {}.",
+                    parent, child
+                );
+            }
+        }
+    }
+
+    fn recalculate_down(&mut self) {
+        d_ary_sift_down::<_, _, D>(&mut self.elements, 0, &self.comparator);
+    }
+
+    fn recalculate_up(&mut self) {
+        let index = self.elements.len() - 1;
+        d_ary_sift_up::<_, _, D>(&mut self.elements, index, &self.comparator);
+    }
+}
+
+pub struct PeekMut<'a, T, C: Compare<T>, const D: usize = 2> {
+    heap: &'a mut Heap<T, C, D>,
+    sift: bool,
+}
+
+impl<'a, T, C: Compare<T>, const D: usize> PeekMut<'a, T, C, D> {
+    /// Removes the peeked element from the heap and returns it.
+    pub fn pop(mut this: PeekMut<'a, T, C, D>) -> T {
+        this.sift = false;
+
+        let result = this.heap.elements.swap_remove(0);
+
+        this.heap.recalculate_down();
+
+        result
+    }
+}
+
+impl<'a, T, C: Compare<T>, const D: usize> Deref for PeekMut<'a, T, C, D> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.heap.elements[0]
+    }
+}
+
+impl<'a, T, C: Compare<T>, const D: usize> DerefMut for PeekMut<'a, T, C, D> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.sift = true;
+        &mut self.heap.elements[0]
+    }
+}
+
+impl<'a, T, C: Compare<T>, const D: usize> Drop for PeekMut<'a, T, C, D> {
+    fn drop(&mut self) {
+        if self.sift {
+            self.heap.recalculate_down();
+        }
+    }
+}
+
+impl<'a, T: fmt::Debug, C: Compare<T>, const D: usize> fmt::Debug for PeekMut<'a, T, C, D> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("PeekMut")
+            .field(&self.heap.elements[0])
+            .finish()
+    }
+}
+
+pub struct IterSorted<'a, T, C, const D: usize = 2> {
+    frontier: Heap<usize, IndexOrder<'a, T, C>>,
+}
+
+impl<'a, T, C: Compare<T>, const D: usize> Iterator for IterSorted<'a, T, C, D> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.frontier.pop().ok()?;
+        let elements = self.frontier.comparator.elements;
+        let first_child_index = get_first_child_index::<D>(index);
+        for child_index in first_child_index..elements.len().min(first_child_index + D) {
+            self.frontier.elements.push(child_index);
+            self.frontier.recalculate_up();
+        }
+
+        Some(&elements[index])
+    }
+}
+
+/// Orders indices into `elements` by the elements they point at.
+struct IndexOrder<'a, T, C> {
+    elements: &'a [T],
+    comparator: &'a C,
+}
+
+impl<'a, T, C: Compare<T>> Compare<usize> for IndexOrder<'a, T, C> {
+    fn compare(&self, a: &usize, b: &usize) -> Ordering {
+        self.comparator
+            .compare(&self.elements[*a], &self.elements[*b])
+    }
+}
+
+pub struct DrainSorted<'a, T, C: Compare<T>, const D: usize = 2> {
+    heap: &'a mut Heap<T, C, D>,
+}
+
+impl<'a, T, C: Compare<T>, const D: usize> Iterator for DrainSorted<'a, T, C, D> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.heap.pop().ok()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let size = self.heap.elements.len();
+        (size, Some(size))
+    }
+}
+
+impl<'a, T, C: Compare<T>, const D: usize> ExactSizeIterator for DrainSorted<'a, T, C, D> {}
+
+impl<'a, T, C: Compare<T>, const D: usize> Drop for DrainSorted<'a, T, C, D> {
+    fn drop(&mut self) {
+        self.heap.elements.clear();
+    }
+}
+
+/// Rebuilding costs about `2 * (size + other_size)` comparisons, while adding
+/// the other elements one by one costs about `log2(size + other_size)` each.
+fn better_to_rebuild(size: usize, other_size: usize) -> bool {
+    let total_size = size + other_size;
+    let log2_total_size = (usize::BITS - total_size.leading_zeros()) as usize;
+
+    2 * total_size < other_size * log2_total_size
+}
+
+impl<T: Ord> Default for Heap<T> {
+    fn default() -> Self {
+        Heap::with_capacity(0)
+    }
+}
+
+impl<T: fmt::Debug, C, const D: usize> fmt::Debug for Heap<T, C, D> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.elements.iter()).finish()
+    }
+}
+
+impl<T: Ord> From<Vec<T>> for Heap<T> {
+    fn from(elements: Vec<T>) -> Self {
+        Heap::from_vec(elements, HeapProperty::Min)
+    }
+}
+
+impl<T: Ord, const D: usize> Default for Heap<T, MinOrder, D> {
+    fn default() -> Self {
+        Heap::growable(0)
+    }
+}
+
+impl<T: Ord, const D: usize> Default for Heap<T, MaxOrder, D> {
+    fn default() -> Self {
+        Heap::growable(0)
+    }
+}
+
+impl<T: Ord, const D: usize> From<Vec<T>> for Heap<T, MinOrder, D> {
+    fn from(elements: Vec<T>) -> Self {
+        Heap::growable_from_vec(elements, MinOrder)
+    }
+}
+
+impl<T: Ord, const D: usize> From<Vec<T>> for Heap<T, MaxOrder, D> {
+    fn from(elements: Vec<T>) -> Self {
+        Heap::growable_from_vec(elements, MaxOrder)
+    }
+}
+
+impl<T: Ord> FromIterator<T> for Heap<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Heap::from(iter.into_iter().collect::<Vec<T>>())
+    }
+}
+
+impl<T: Ord, const D: usize> FromIterator<T> for Heap<T, MinOrder, D> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Heap::from(iter.into_iter().collect::<Vec<T>>())
+    }
+}
+
+impl<T: Ord, const D: usize> FromIterator<T> for Heap<T, MaxOrder, D> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Heap::from(iter.into_iter().collect::<Vec<T>>())
+    }
+}
+
+/// Adds every element of the iterator to the heap.
+///
+/// # Panics
+///
+/// Panics if the heap is bounded and runs out of room.
+impl<T, C: Compare<T>, const D: usize> Extend<T> for Heap<T, C, D> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for element in iter {
+            if let Err(error) = self.add(element) {
+                panic!("{}", error);
+            }
+        }
+    }
+}
+
+impl<'a, T: Copy + 'a, C: Compare<T>, const D: usize> Extend<&'a T> for Heap<T, C, D> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+/// Consumes the heap, yielding its elements in arbitrary order.
+impl<T, C, const D: usize> IntoIterator for Heap<T, C, D> {
+    type Item = T;
+    type IntoIter = vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.elements.into_iter()
+    }
+}
+
+impl<'a, T, C, const D: usize> IntoIterator for &'a Heap<T, C, D> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.elements.iter()
+    }
+}
+
+#[cfg(test)]
+mod heap_constructor_tests {
+    use super::{Heap, HeapProperty};
+
+    fn create_heap(size: usize) -> Heap<i32> {
+        Heap::new(size)
+    }
+
+    fn create_heap_as(size: usize, property: HeapProperty) -> Heap<i32> {
+        Heap::new_as(size, property)
+    }
+
+    fn assert_constructor(heap: &Heap<i32>, size: usize, expected_property: HeapProperty) {
+        assert_eq!(expected_property, heap.comparator);
+        assert_eq!(Some(size), heap.bound);
+        assert_eq!(size, heap.capacity());
+        assert!(heap.elements.capacity() >= size);
+        assert!(heap.is_bounded());
+        assert!(heap.elements.is_empty());
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn default_constructor_with_zero() {
+        let size = 0;
+        let heap = create_heap(size);
+        assert_constructor(&heap, size, HeapProperty::Min);
+    }
+
+    #[test]
+    fn default_constructor_with_one() {
+        let size = 1;
+        let heap = create_heap(size);
+        assert_constructor(&heap, size, HeapProperty::Min);
+    }
+
+    #[test]
+    fn default_constructor_with_five() {
+        let size = 5;
+        let heap = create_heap(size);
+        assert_constructor(&heap, size, HeapProperty::Min);
+    }
+
+    #[test]
+    fn constructor_with_zero_and_min() {
+        let size = 0;
+        let property = HeapProperty::Min;
+        let heap = create_heap_as(size, property);
+        assert_constructor(&heap, size, property);
+    }
+
+    #[test]
+    fn default_constructor_with_one_and_min() {
+        let size = 1;
+        let property = HeapProperty::Min;
+        let heap = create_heap_as(size, property);
+        assert_constructor(&heap, size, property);
+    }
+
+    #[test]
+    fn default_constructor_with_five_and_min() {
+        let size = 5;
+        let property = HeapProperty::Min;
+        let heap = create_heap_as(size, property);
+        assert_constructor(&heap, size, property);
+    }
+}
+
+#[cfg(test)]
+mod growable_heap_tests {
+    use super::{Heap, HeapError, HeapProperty};
+
+    #[test]
+    fn constructor_with_capacity() {
+        let heap: Heap<i32> = Heap::with_capacity(5);
+
+        assert_eq!(None, heap.bound);
+        assert_eq!(HeapProperty::Min, heap.comparator);
+        assert!(!heap.is_bounded());
+        assert!(heap.capacity() >= 5);
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn add_beyond_initial_capacity() {
+        let mut heap = Heap::with_capacity_as(0, HeapProperty::Max);
+        for element in 0..100 {
+            heap.add(element).unwrap();
+        }
+
+        assert!(heap.capacity() >= 100);
+        for expected in (0..100).rev() {
+            assert_eq!(expected, heap.pop().unwrap());
+        }
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn reserve_and_shrink_to_fit() {
+        let mut heap = Heap::with_capacity(0);
+        heap.reserve(10);
+
+        assert!(heap.capacity() >= 10);
+
+        heap.add(1).unwrap();
+        heap.shrink_to_fit();
+
+        assert!(heap.capacity() >= 1);
+        assert!(heap.capacity() < 10);
+    }
+
+    #[test]
+    fn reserve_and_shrink_to_fit_on_bounded_heap() {
+        let mut heap = Heap::new(3);
+        heap.add(1).unwrap();
+        heap.reserve(10);
+        heap.shrink_to_fit();
+
+        assert_eq!(3, heap.capacity());
+        heap.add(2).unwrap();
+        heap.add(3).unwrap();
+        assert_eq!(HeapError::Full { capacity: 3 }, heap.add(4).unwrap_err());
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test_util {
+    use super::{precedes, Heap, HeapError};
+
+    pub fn assert_heap_order(heap: &Heap<i32>) {
+        for index in 1..heap.elements.len() {
+            let parent = &heap.elements[(index - 1) / 2];
+            assert!(
+                precedes(&heap.comparator, parent, &heap.elements[index])
+                    || *parent == heap.elements[index]
+            );
+        }
+    }
+
+    pub fn assert_peek_on_empty_heap(heap: &Heap<i32>) {
+        let result = heap.peek();
+
+        assert!(result.is_err());
+        assert_eq!(HeapError::Empty, result.unwrap_err());
+    }
+
+    pub fn assert_ok_result(result: Result<(), HeapError>) {
+        assert!(result.is_ok());
+        assert_eq!((), result.unwrap());
+    }
+
+    pub fn assert_value_result(result: Result<i32, HeapError>, value: i32) {
+        assert!(result.is_ok());
+        assert_eq!(value, result.unwrap());
+    }
+
+    pub fn assert_non_empty_heap(heap: &Heap<i32>, elements: Vec<i32>, size: usize) {
+        assert_eq!(elements, heap.elements);
+        assert_eq!(size, heap.len());
+        assert!(!heap.is_empty());
+
+        let peek_result = heap.peek().copied();
+
+        assert_value_result(peek_result, elements[0]);
+    }
+
+    /// Returns the numbers in `0..size` in a fixed scrambled order. Stepping
+    /// by the prime 7919 visits every number once.
+    pub fn shuffled(size: i32) -> Vec<i32> {
+        (0..size).map(|i| (i * 7919) % size).collect()
+    }
+
+    pub fn assert_empty_heap(heap: &Heap<i32>) {
+        assert!(heap.elements.is_empty());
+        assert_eq!(0, heap.len());
+        assert!(heap.is_empty());
+        assert!(!heap.is_full());
+
+        assert_peek_on_empty_heap(heap);
+    }
+}
+
+#[cfg(test)]
+mod min_heap_tests {
+    use super::test_util;
+    use super::{Heap, HeapError, HeapProperty};
+
+    #[test]
+    fn peek_on_heap_with_size_as_zero() {
+        let heap = Heap::new_as(0, HeapProperty::Min);
+        test_util::assert_peek_on_empty_heap(&heap);
+    }
+
+    #[test]
+    fn peek_on_heap_with_size_as_five() {
+        let heap = Heap::new_as(5, HeapProperty::Min);
+        test_util::assert_peek_on_empty_heap(&heap);
+    }
+
+    #[test]
+    fn add_six_elements_as_random() {
+        let mut heap = Heap::new_as(6, HeapProperty::Min);
+
+        let result = heap.add(5);
+
+        test_util::assert_ok_result(result);
+        test_util::assert_non_empty_heap(&heap, vec![5], 1);
+
+        let result = heap.add(3);
+
+        test_util::assert_ok_result(result);
+        test_util::assert_non_empty_heap(&heap, vec![3, 5], 2);
+
+        let result = heap.add(9);
+
+        test_util::assert_ok_result(result);
+        test_util::assert_non_empty_heap(&heap, vec![3, 5, 9], 3);
+
+        let result = heap.add(8);
+
+        test_util::assert_ok_result(result);
+        test_util::assert_non_empty_heap(&heap, vec![3, 5, 9, 8], 4);
+
+        let result = heap.add(1);
+
+        test_util::assert_ok_result(result);
+        test_util::assert_non_empty_heap(&heap, vec![1, 3, 9, 8, 5], 5);
+
+        let result = heap.add(6);
+
+        test_util::assert_ok_result(result);
+        test_util::assert_non_empty_heap(&heap, vec![1, 3, 6, 8, 5, 9], 6);
+
+        let result = heap.add(0);
+
+        assert!(result.is_err());
+        assert_eq!(HeapError::Full { capacity: 6 }, result.unwrap_err());
+        test_util::assert_non_empty_heap(&heap, vec![1, 3, 6, 8, 5, 9], 6);
+    }
+
+    #[test]
+    fn add_six_elements_as_sequential() {
+        let mut heap = Heap::new_as(6, HeapProperty::Min);
+
+        let result = heap.add(1);
+
+        test_util::assert_ok_result(result);
+        test_util::assert_non_empty_heap(&heap, vec![1], 1);
+
+        let result = heap.add(2);
+
+        test_util::assert_ok_result(result);
+        test_util::assert_non_empty_heap(&heap, vec![1, 2], 2);
+
+        let result = heap.add(3);
+
+        test_util::assert_ok_result(result);
+        test_util::assert_non_empty_heap(&heap, vec![1, 2, 3], 3);
+
+        let result = heap.add(4);
+
+        test_util::assert_ok_result(result);
+        test_util::assert_non_empty_heap(&heap, vec![1, 2, 3, 4], 4);
+
+        let result = heap.add(5);
+
+        test_util::assert_ok_result(result);
+        test_util::assert_non_empty_heap(&heap, vec![1, 2, 3, 4, 5], 5);
+
+        let result = heap.add(6);
+
+        test_util::assert_ok_result(result);
+        test_util::assert_non_empty_heap(&heap, vec![1, 2, 3, 4, 5, 6], 6);
+
+        let result = heap.add(0);
+
+        assert!(result.is_err());
+        assert_eq!(HeapError::Full { capacity: 6 }, result.unwrap_err());
+        test_util::assert_non_empty_heap(&heap, vec![1, 2, 3, 4, 5, 6], 6);
+    }
+
+    #[test]
+    fn pop_from_six_elements_as_random() {
+        let mut heap = Heap::new_as(6, HeapProperty::Min);
+        heap.add(5).unwrap();
+        heap.add(3).unwrap();
+        heap.add(9).unwrap();
+        heap.add(8).unwrap();
+        heap.add(1).unwrap();
+        heap.add(6).unwrap();
+        test_util::assert_non_empty_heap(&heap, vec![1, 3, 6, 8, 5, 9], 6);
+
+        let result = heap.pop();
+
+        test_util::assert_value_result(result, 1);
+        test_util::assert_non_empty_heap(&heap, vec![3, 5, 6, 8, 9], 5);
+
+        let result = heap.pop();
+
+        test_util::assert_value_result(result, 3);
+        test_util::assert_non_empty_heap(&heap, vec![5, 8, 6, 9], 4);
+
+        let result = heap.pop();
+
+        test_util::assert_value_result(result, 5);
+        test_util::assert_non_empty_heap(&heap, vec![6, 8, 9], 3);
+
+        let result = heap.pop();
+
+        test_util::assert_value_result(result, 6);
+        test_util::assert_non_empty_heap(&heap, vec![8, 9], 2);
+
+        let result = heap.pop();
+
+        test_util::assert_value_result(result, 8);
+        test_util::assert_non_empty_heap(&heap, vec![9], 1);
+
+        let result = heap.pop();
+
+        test_util::assert_value_result(result, 9);
+        test_util::assert_empty_heap(&heap);
+
+        let result = heap.pop();
+
+        assert!(result.is_err());
+        assert_eq!(HeapError::Empty, result.unwrap_err());
+        test_util::assert_empty_heap(&heap);
+    }
+
+    #[test]
+    fn pop_from_six_elements_as_sequential() {
+        let mut heap = Heap::new_as(6, HeapProperty::Min);
+        heap.add(1).unwrap();
+        heap.add(2).unwrap();
+        heap.add(3).unwrap();
+        heap.add(4).unwrap();
+        heap.add(5).unwrap();
+        heap.add(6).unwrap();
+        test_util::assert_non_empty_heap(&heap, vec![1, 2, 3, 4, 5, 6], 6);
+
+        let result = heap.pop();
+
+        test_util::assert_value_result(result, 1);
+        test_util::assert_non_empty_heap(&heap, vec![2, 4, 3, 6, 5], 5);
+
+        let result = heap.pop();
+
+        test_util::assert_value_result(result, 2);
+        test_util::assert_non_empty_heap(&heap, vec![3, 4, 5, 6], 4);
+
+        let result = heap.pop();
+
+        test_util::assert_value_result(result, 3);
+        test_util::assert_non_empty_heap(&heap, vec![4, 6, 5], 3);
+
+        let result = heap.pop();
+
+        test_util::assert_value_result(result, 4);
+        test_util::assert_non_empty_heap(&heap, vec![5, 6], 2);
+
+        let result = heap.pop();
+
+        test_util::assert_value_result(result, 5);
+        test_util::assert_non_empty_heap(&heap, vec![6], 1);
+
+        let result = heap.pop();
+
+        test_util::assert_value_result(result, 6);
+        test_util::assert_empty_heap(&heap);
+
+        let result = heap.pop();
+
+        assert!(result.is_err());
+        assert_eq!(HeapError::Empty, result.unwrap_err());
+        test_util::assert_empty_heap(&heap);
+    }
+}
+
+#[cfg(test)]
+mod max_heap_tests {
+    use super::test_util;
+    use super::{Heap, HeapError, HeapProperty};
+
+    #[test]
+    fn peek_on_heap_with_size_as_zero() {
+        let heap = Heap::new_as(0, HeapProperty::Max);
+        test_util::assert_peek_on_empty_heap(&heap);
+    }
+
+    #[test]
+    fn peek_on_heap_with_size_as_five() {
+        let heap = Heap::new_as(5, HeapProperty::Max);
+        test_util::assert_peek_on_empty_heap(&heap);
+    }
+
+    #[test]
+    fn add_six_elements_as_random() {
+        let mut heap = Heap::new_as(6, HeapProperty::Max);
+
+        let result = heap.add(5);
+
+        test_util::assert_ok_result(result);
+        test_util::assert_non_empty_heap(&heap, vec![5], 1);
+
+        let result = heap.add(3);
+
+        test_util::assert_ok_result(result);
+        test_util::assert_non_empty_heap(&heap, vec![5, 3], 2);
+
+        let result = heap.add(9);
+
+        test_util::assert_ok_result(result);
+        test_util::assert_non_empty_heap(&heap, vec![9, 3, 5], 3);
+
+        let result = heap.add(8);
+
+        test_util::assert_ok_result(result);
+        test_util::assert_non_empty_heap(&heap, vec![9, 8, 5, 3], 4);
+
+        let result = heap.add(1);
+
+        test_util::assert_ok_result(result);
+        test_util::assert_non_empty_heap(&heap, vec![9, 8, 5, 3, 1], 5);
+
+        let result = heap.add(6);
+
+        test_util::assert_ok_result(result);
+        test_util::assert_non_empty_heap(&heap, vec![9, 8, 6, 3, 1, 5], 6);
+
+        let result = heap.add(0);
+
+        assert!(result.is_err());
+        assert_eq!(HeapError::Full { capacity: 6 }, result.unwrap_err());
+        test_util::assert_non_empty_heap(&heap, vec![9, 8, 6, 3, 1, 5], 6);
+    }
+
+    #[test]
+    fn add_six_elements_as_sequential() {
+        let mut heap = Heap::new_as(6, HeapProperty::Max);
+
+        let result = heap.add(1);
+
+        test_util::assert_ok_result(result);
+        test_util::assert_non_empty_heap(&heap, vec![1], 1);
+
+        let result = heap.add(2);
+
+        test_util::assert_ok_result(result);
+        test_util::assert_non_empty_heap(&heap, vec![2, 1], 2);
+
+        let result = heap.add(3);
+
+        test_util::assert_ok_result(result);
+        test_util::assert_non_empty_heap(&heap, vec![3, 1, 2], 3);
+
+        let result = heap.add(4);
+
+        test_util::assert_ok_result(result);
+        test_util::assert_non_empty_heap(&heap, vec![4, 3, 2, 1], 4);
+
+        let result = heap.add(5);
+
+        test_util::assert_ok_result(result);
+        test_util::assert_non_empty_heap(&heap, vec![5, 4, 2, 1, 3], 5);
+
+        let result = heap.add(6);
+
+        test_util::assert_ok_result(result);
+        test_util::assert_non_empty_heap(&heap, vec![6, 4, 5, 1, 3, 2], 6);
+
+        let result = heap.add(0);
+
+        assert!(result.is_err());
+        assert_eq!(HeapError::Full { capacity: 6 }, result.unwrap_err());
+        test_util::assert_non_empty_heap(&heap, vec![6, 4, 5, 1, 3, 2], 6);
+    }
+
+    #[test]
+    fn pop_from_six_elements_as_random() {
+        let mut heap = Heap::new_as(6, HeapProperty::Max);
+        heap.add(5).unwrap();
+        heap.add(3).unwrap();
+        heap.add(9).unwrap();
+        heap.add(8).unwrap();
+        heap.add(1).unwrap();
+        heap.add(6).unwrap();
+        test_util::assert_non_empty_heap(&heap, vec![9, 8, 6, 3, 1, 5], 6);
+
+        let result = heap.pop();
+
+        test_util::assert_value_result(result, 9);
+        test_util::assert_non_empty_heap(&heap, vec![8, 5, 6, 3, 1], 5);
+
+        let result = heap.pop();
+
+        test_util::assert_value_result(result, 8);
+        test_util::assert_non_empty_heap(&heap, vec![6, 5, 1, 3], 4);
+
+        let result = heap.pop();
+
+        test_util::assert_value_result(result, 6);
+        test_util::assert_non_empty_heap(&heap, vec![5, 3, 1], 3);
+
+        let result = heap.pop();
+
+        test_util::assert_value_result(result, 5);
+        test_util::assert_non_empty_heap(&heap, vec![3, 1], 2);
+
+        let result = heap.pop();
+
+        test_util::assert_value_result(result, 3);
+        test_util::assert_non_empty_heap(&heap, vec![1], 1);
+
+        let result = heap.pop();
+
+        test_util::assert_value_result(result, 1);
+        test_util::assert_empty_heap(&heap);
+
+        let result = heap.pop();
+
+        assert!(result.is_err());
+        assert_eq!(HeapError::Empty, result.unwrap_err());
+        test_util::assert_empty_heap(&heap);
+    }
+
+    #[test]
+    fn pop_from_six_elements_as_sequential() {
+        let mut heap = Heap::new_as(6, HeapProperty::Max);
+        heap.add(1).unwrap();
+        heap.add(2).unwrap();
+        heap.add(3).unwrap();
+        heap.add(4).unwrap();
+        heap.add(5).unwrap();
+        heap.add(6).unwrap();
+        test_util::assert_non_empty_heap(&heap, vec![6, 4, 5, 1, 3, 2], 6);
+
+        let result = heap.pop();
+
+        test_util::assert_value_result(result, 6);
+        test_util::assert_non_empty_heap(&heap, vec![5, 4, 2, 1, 3], 5);
+
+        let result = heap.pop();
+
+        test_util::assert_value_result(result, 5);
+        test_util::assert_non_empty_heap(&heap, vec![4, 3, 2, 1], 4);
+
+        let result = heap.pop();
+
+        test_util::assert_value_result(result, 4);
+        test_util::assert_non_empty_heap(&heap, vec![3, 1, 2], 3);
+
+        let result = heap.pop();
+
+        test_util::assert_value_result(result, 3);
+        test_util::assert_non_empty_heap(&heap, vec![2, 1], 2);
+
+        let result = heap.pop();
+
+        test_util::assert_value_result(result, 2);
+        test_util::assert_non_empty_heap(&heap, vec![1], 1);
+
+        let result = heap.pop();
+
+        test_util::assert_value_result(result, 1);
+        test_util::assert_empty_heap(&heap);
+
+        let result = heap.pop();
+
+        assert!(result.is_err());
+        assert_eq!(HeapError::Empty, result.unwrap_err());
+        test_util::assert_empty_heap(&heap);
+    }
+}
+
+#[cfg(test)]
+mod generic_heap_tests {
+    use super::{Heap, HeapProperty};
+
+    #[test]
+    fn pop_strings_in_order() {
+        let mut heap = Heap::new_as(3, HeapProperty::Min);
+        heap.add("pear".to_string()).unwrap();
+        heap.add("apple".to_string()).unwrap();
+        heap.add("fig".to_string()).unwrap();
+
+        assert_eq!("apple", heap.peek().unwrap());
+        assert_eq!("apple".to_string(), heap.pop().unwrap());
+        assert_eq!("fig".to_string(), heap.pop().unwrap());
+        assert_eq!("pear".to_string(), heap.pop().unwrap());
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn pop_tuples_in_order() {
+        let mut heap = Heap::new_as(3, HeapProperty::Max);
+        heap.add((2, 'b')).unwrap();
+        heap.add((3, 'a')).unwrap();
+        heap.add((2, 'c')).unwrap();
+
+        assert_eq!((3, 'a'), heap.pop().unwrap());
+        assert_eq!((2, 'c'), heap.pop().unwrap());
+        assert_eq!((2, 'b'), heap.pop().unwrap());
+    }
+}
+
+#[cfg(test)]
+mod comparator_heap_tests {
+    use super::{Compare, Heap, HeapProperty};
+    use std::cmp::Ordering;
+
+    #[derive(Debug, PartialEq)]
+    struct Task {
+        deadline: u32,
+        priority: u8,
+    }
+
+    fn task(deadline: u32, priority: u8) -> Task {
+        Task { deadline, priority }
+    }
+
+    #[test]
+    fn closure_orders_by_deadline_then_priority() {
+        let mut heap = Heap::with_comparator(4, |a: &Task, b: &Task| {
+            a.deadline
+                .cmp(&b.deadline)
+                .then_with(|| b.priority.cmp(&a.priority))
+        });
+        heap.add(task(20, 1)).unwrap();
+        heap.add(task(10, 1)).unwrap();
+        heap.add(task(10, 5)).unwrap();
+        heap.add(task(15, 9)).unwrap();
+
+        assert_eq!(task(10, 5), heap.pop().unwrap());
+        assert_eq!(task(10, 1), heap.pop().unwrap());
+        assert_eq!(task(15, 9), heap.pop().unwrap());
+        assert_eq!(task(20, 1), heap.pop().unwrap());
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn boxed_trait_object_comparator() {
+        let comparator: Box<dyn Compare<i32>> = Box::new(HeapProperty::Max);
+        let mut heap = Heap::with_comparator(3, comparator);
+        heap.add(1).unwrap();
+        heap.add(7).unwrap();
+        heap.add(4).unwrap();
+
+        assert_eq!(7, heap.pop().unwrap());
+        assert_eq!(4, heap.pop().unwrap());
+        assert_eq!(1, heap.pop().unwrap());
+    }
+
+    #[test]
+    fn borrowed_trait_object_comparator() {
+        let reverse = |a: &i32, b: &i32| b.cmp(a);
+        let comparator: &dyn Compare<i32> = &reverse;
+        let mut heap = Heap::with_comparator(3, comparator);
+        heap.add(2).unwrap();
+        heap.add(9).unwrap();
+
+        assert_eq!(9, heap.pop().unwrap());
+    }
+
+    #[test]
+    fn heap_property_as_comparator() {
+        assert_eq!(Ordering::Less, HeapProperty::Min.compare(&1, &2));
+        assert_eq!(Ordering::Greater, HeapProperty::Max.compare(&1, &2));
+        assert_eq!(Ordering::Equal, HeapProperty::Max.compare(&2, &2));
+    }
+}
+
+#[cfg(test)]
+mod key_heap_tests {
+    use super::{Heap, HeapError, HeapProperty};
+
+    #[derive(Debug, PartialEq)]
+    struct Job {
+        name: &'static str,
+        deadline: u32,
+    }
+
+    fn job(name: &'static str, deadline: u32) -> Job {
+        Job { name, deadline }
+    }
+
+    #[test]
+    fn by_key_as_min() {
+        let mut heap = Heap::by_key(|job: &Job| job.deadline, HeapProperty::Min);
+        heap.add(job("build", 30)).unwrap();
+        heap.add(job("deploy", 50)).unwrap();
+        heap.add(job("test", 10)).unwrap();
+
+        assert_eq!(job("test", 10), heap.pop().unwrap());
+        assert_eq!(job("build", 30), heap.pop().unwrap());
+        assert_eq!(job("deploy", 50), heap.pop().unwrap());
+    }
+
+    #[test]
+    fn by_key_as_max() {
+        let mut heap = Heap::by_key(|job: &Job| job.deadline, HeapProperty::Max);
+        heap.add(job("build", 30)).unwrap();
+        heap.add(job("deploy", 50)).unwrap();
+        heap.add(job("test", 10)).unwrap();
+
+        assert_eq!(job("deploy", 50), heap.pop().unwrap());
+        assert_eq!(job("build", 30), heap.pop().unwrap());
+        assert_eq!(job("test", 10), heap.pop().unwrap());
+    }
+
+    #[test]
+    fn by_key_with_derived_key() {
+        let mut heap = Heap::by_key(|name: &String| name.len(), HeapProperty::Min);
+        heap.add("three".to_string()).unwrap();
+        heap.add("one".to_string()).unwrap();
+        heap.add("eleven".to_string()).unwrap();
+
+        assert_eq!("one".to_string(), heap.pop().unwrap());
+        assert_eq!("three".to_string(), heap.pop().unwrap());
+        assert_eq!("eleven".to_string(), heap.pop().unwrap());
+    }
+
+    #[test]
+    fn by_key_is_growable() {
+        let mut heap = Heap::by_key(|job: &Job| job.deadline, HeapProperty::Min);
+        for deadline in (0..100).rev() {
+            heap.add(job("build", deadline)).unwrap();
+        }
+
+        assert!(!heap.is_bounded());
+        assert_eq!(100, heap.len());
+        assert_eq!(job("build", 0), heap.pop().unwrap());
+    }
+
+    #[test]
+    fn by_key_bounded_rejects_extra_element() {
+        let mut heap = Heap::by_key_bounded(2, |job: &Job| job.deadline, HeapProperty::Max);
+        heap.add(job("build", 30)).unwrap();
+        heap.add(job("test", 10)).unwrap();
+
+        assert!(heap.is_bounded());
+        assert_eq!(
+            HeapError::Full { capacity: 2 },
+            heap.add(job("deploy", 50)).unwrap_err()
+        );
+        assert_eq!(job("build", 30), heap.pop().unwrap());
+    }
+}
+
+#[cfg(test)]
+mod heap_trait_tests {
+    use super::{Heap, HeapProperty};
+
+    fn sorted(heap: &Heap<i32>) -> Vec<i32> {
+        let mut elements: Vec<i32> = heap.iter().copied().collect();
+        elements.sort_unstable();
+        elements
+    }
+
+    #[test]
+    fn default_is_empty_growable_min_heap() {
+        let heap: Heap<i32> = Heap::default();
+
+        assert!(heap.is_empty());
+        assert!(!heap.is_bounded());
+        assert_eq!(HeapProperty::Min, heap.comparator);
+    }
+
+    #[test]
+    fn collect_from_iterator() {
+        let mut heap: Heap<i32> = vec![5, 3, 9, 8, 1, 6].into_iter().collect();
+
+        assert_eq!(vec![1, 3, 5, 6, 8, 9], sorted(&heap));
+        assert_eq!(1, heap.pop().unwrap());
+        assert_eq!(3, heap.pop().unwrap());
+    }
+
+    #[test]
+    fn extend_with_values_and_references() {
+        let mut heap = Heap::with_capacity_as(0, HeapProperty::Max);
+        heap.extend(vec![5, 3]);
+        heap.extend(&[9, 1]);
+
+        assert_eq!(vec![1, 3, 5, 9], sorted(&heap));
+        assert_eq!(9, heap.pop().unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "Full heap.")]
+    fn extend_bounded_heap_beyond_size() {
+        let mut heap = Heap::new(2);
+        heap.extend(vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn clone_is_independent_snapshot() {
+        let mut heap = Heap::new(3);
+        heap.add(2).unwrap();
+        heap.add(1).unwrap();
+
+        let snapshot = heap.clone();
+        heap.pop().unwrap();
+
+        assert_eq!(vec![1, 2], sorted(&snapshot));
+        assert_eq!(vec![2], sorted(&heap));
+        assert!(snapshot.is_bounded());
+    }
+
+    #[test]
+    fn debug_lists_live_elements() {
+        let mut heap = Heap::new(4);
+        heap.add(2).unwrap();
+        heap.add(1).unwrap();
+        heap.add(3).unwrap();
+        heap.pop().unwrap();
+
+        assert_eq!("[2, 3]", format!("{:?}", heap));
+    }
+
+    #[test]
+    fn into_iter_covers_live_elements() {
+        let mut heap = Heap::new(4);
+        heap.add(4).unwrap();
+        heap.add(7).unwrap();
+        heap.add(5).unwrap();
+        heap.pop().unwrap();
+
+        let mut borrowed: Vec<i32> = (&heap).into_iter().copied().collect();
+        borrowed.sort_unstable();
+        let mut owned: Vec<i32> = heap.into_iter().collect();
+        owned.sort_unstable();
+
+        assert_eq!(vec![5, 7], borrowed);
+        assert_eq!(vec![5, 7], owned);
+    }
+}
+
+#[cfg(test)]
+mod heapify_tests {
+    use super::test_util;
+    use super::{Heap, HeapProperty};
+
+    fn drain(mut heap: Heap<i32>) -> Vec<i32> {
+        let mut elements = Vec::new();
+        while let Ok(element) = heap.pop() {
+            elements.push(element);
+        }
+        elements
+    }
+
+    #[test]
+    fn from_vec_as_min() {
+        let heap = Heap::from_vec(vec![5, 3, 9, 8, 1, 6], HeapProperty::Min);
+
+        assert_eq!(vec![1, 3, 6, 8, 5, 9], heap.elements);
+        assert!(!heap.is_bounded());
+        test_util::assert_heap_order(&heap);
+        assert_eq!(vec![1, 3, 5, 6, 8, 9], drain(heap));
+    }
+
+    #[test]
+    fn from_vec_as_max() {
+        let heap = Heap::from_vec(vec![1, 2, 3, 4, 5, 6], HeapProperty::Max);
+
+        assert_eq!(vec![6, 5, 3, 4, 2, 1], heap.elements);
+        test_util::assert_heap_order(&heap);
+        assert_eq!(vec![6, 5, 4, 3, 2, 1], drain(heap));
+    }
+
+    #[test]
+    fn from_vec_with_duplicates() {
+        let heap = Heap::from_vec(vec![4, 1, 4, 1, 4, 1, 4], HeapProperty::Max);
+
+        test_util::assert_heap_order(&heap);
+        assert_eq!(vec![4, 4, 4, 4, 1, 1, 1], drain(heap));
+    }
+
+    #[test]
+    fn from_empty_vec() {
+        let heap: Heap<i32> = Heap::from(Vec::new());
+
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn from_large_vec() {
+        let heap: Heap<i32> = Heap::from(test_util::shuffled(1000));
+
+        test_util::assert_heap_order(&heap);
+        assert_eq!((0..1000).collect::<Vec<i32>>(), drain(heap));
+    }
+}
+
+#[cfg(test)]
+mod heap_sort_tests {
+    use super::{test_util, Heap, HeapProperty};
+    use crate::heap_sort;
+
+    #[test]
+    fn into_sorted_vec_as_min() {
+        let heap = Heap::from_vec(vec![5, 3, 9, 8, 1, 6], HeapProperty::Min);
+
+        assert_eq!(vec![1, 3, 5, 6, 8, 9], heap.into_sorted_vec());
+    }
+
+    #[test]
+    fn into_sorted_vec_as_max() {
+        let mut heap = Heap::new_as(6, HeapProperty::Max);
+        for element in [5, 3, 9, 8, 1, 6] {
+            heap.add(element).unwrap();
+        }
+
+        assert_eq!(vec![9, 8, 6, 5, 3, 1], heap.into_sorted_vec());
+    }
+
+    #[test]
+    fn into_sorted_vec_reuses_buffer() {
+        let mut heap = Heap::from_vec(Vec::with_capacity(64), HeapProperty::Min);
+        heap.extend(vec![3, 1, 2]);
+
+        let sorted = heap.into_sorted_vec();
+
+        assert_eq!(vec![1, 2, 3], sorted);
+        assert!(sorted.capacity() >= 64);
+    }
+
+    #[test]
+    fn into_sorted_vec_of_empty_heap() {
+        let heap: Heap<i32> = Heap::new(3);
+
+        assert!(heap.into_sorted_vec().is_empty());
+    }
+
+    #[test]
+    fn heap_sort_as_min() {
+        let mut elements = [5, 3, 9, 8, 1, 6, 3];
+        heap_sort(&mut elements, HeapProperty::Min);
+
+        assert_eq!([1, 3, 3, 5, 6, 8, 9], elements);
+    }
+
+    #[test]
+    fn heap_sort_as_max() {
+        let mut elements = vec!["b", "d", "a", "c"];
+        heap_sort(&mut elements, HeapProperty::Max);
+
+        assert_eq!(vec!["d", "c", "b", "a"], elements);
+    }
+
+    #[test]
+    fn heap_sort_of_short_slices() {
+        let mut empty: [i32; 0] = [];
+        heap_sort(&mut empty, HeapProperty::Min);
+        let mut single = [1];
+        heap_sort(&mut single, HeapProperty::Max);
+
+        assert_eq!([1], single);
+    }
+
+    #[test]
+    fn heap_sort_of_large_slice() {
+        let mut elements = test_util::shuffled(1000);
+        heap_sort(&mut elements, HeapProperty::Max);
+
+        assert_eq!((0..1000).rev().collect::<Vec<i32>>(), elements);
+    }
+}
+
+#[cfg(test)]
+mod heap_iterator_tests {
+    use super::{Heap, HeapProperty};
+
+    fn create_heap(property: HeapProperty) -> Heap<i32> {
+        Heap::from_vec(vec![5, 3, 9, 8, 1, 6], property)
+    }
+
+    #[test]
+    fn iter_sorted_as_min() {
+        let heap = create_heap(HeapProperty::Min);
+
+        let elements: Vec<i32> = heap.iter_sorted().copied().collect();
+
+        assert_eq!(vec![1, 3, 5, 6, 8, 9], elements);
+        assert_eq!(vec![1, 3, 6, 8, 5, 9], heap.elements);
+    }
+
+    #[test]
+    fn iter_sorted_as_max_partially() {
+        let heap = create_heap(HeapProperty::Max);
+
+        let elements: Vec<i32> = heap.iter_sorted().take(3).copied().collect();
+
+        assert_eq!(vec![9, 8, 6], elements);
+        assert_eq!(6, heap.elements.len());
+    }
+
+    #[test]
+    fn iter_sorted_of_empty_heap() {
+        let heap: Heap<i32> = Heap::new(3);
+
+        assert_eq!(None, heap.iter_sorted().next());
+    }
+
+    #[test]
+    fn iter_sorted_with_duplicates() {
+        let heap = Heap::from_vec(vec![2, 1, 2, 1, 3, 1], HeapProperty::Min);
+
+        let elements: Vec<i32> = heap.iter_sorted().copied().collect();
+
+        assert_eq!(vec![1, 1, 1, 2, 2, 3], elements);
+    }
+
+    #[test]
+    fn drain_in_arbitrary_order() {
+        let mut heap = create_heap(HeapProperty::Min);
+
+        let mut elements: Vec<i32> = heap.drain().collect();
+        elements.sort_unstable();
+
+        assert_eq!(vec![1, 3, 5, 6, 8, 9], elements);
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn drain_keeps_heap_bound() {
+        let mut heap = Heap::new(2);
+        heap.add(1).unwrap();
+        heap.drain();
+
+        assert!(heap.is_empty());
+        assert_eq!(2, heap.capacity());
+    }
+
+    #[test]
+    fn drain_sorted_as_max() {
+        let mut heap = create_heap(HeapProperty::Max);
+
+        let drain = heap.drain_sorted();
+
+        assert_eq!(6, drain.len());
+        assert_eq!(vec![9, 8, 6, 5, 3, 1], drain.collect::<Vec<i32>>());
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn drain_sorted_is_lazy_and_clears_on_drop() {
+        let mut heap = create_heap(HeapProperty::Min);
+
+        {
+            let mut drain = heap.drain_sorted();
+            assert_eq!(Some(1), drain.next());
+            assert_eq!(Some(3), drain.next());
+            assert_eq!(4, drain.len());
+        }
+
+        assert!(heap.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod heap_size_tests {
+    use super::{Heap, HeapProperty};
+
+    #[test]
+    fn len_and_is_full_on_bounded_heap() {
+        let mut heap = Heap::new_as(2, HeapProperty::Max);
+
+        assert_eq!(0, heap.len());
+        assert!(!heap.is_full());
+
+        heap.add(1).unwrap();
+
+        assert_eq!(1, heap.len());
+        assert!(!heap.is_full());
+
+        heap.add(2).unwrap();
+
+        assert_eq!(2, heap.len());
+        assert_eq!(2, heap.capacity());
+        assert!(heap.is_full());
+
+        heap.pop().unwrap();
+
+        assert_eq!(1, heap.len());
+        assert!(!heap.is_full());
+    }
+
+    #[test]
+    fn is_full_on_zero_sized_heap() {
+        let heap: Heap<i32> = Heap::new(0);
+
+        assert!(heap.is_full());
+    }
+
+    #[test]
+    fn growable_heap_is_never_full() {
+        let mut heap = Heap::with_capacity(1);
+        heap.add(1).unwrap();
+
+        assert!(!heap.is_full());
+    }
+
+    #[test]
+    fn clear_keeps_storage() {
+        let mut heap = Heap::with_capacity(8);
+        heap.extend(vec![3, 1, 2]);
+        let capacity = heap.capacity();
+
+        heap.clear();
+
+        assert!(heap.is_empty());
+        assert_eq!(0, heap.len());
+        assert_eq!(capacity, heap.capacity());
+        assert_eq!(capacity, heap.elements.capacity());
+    }
+
+    #[test]
+    fn clear_then_reuse_bounded_heap() {
+        let mut heap = Heap::new(2);
+        heap.add(1).unwrap();
+        heap.add(2).unwrap();
+
+        heap.clear();
+        heap.add(4).unwrap();
+        heap.add(3).unwrap();
+
+        assert!(heap.is_full());
+        assert_eq!(3, heap.pop().unwrap());
+    }
+}
+
+#[cfg(test)]
+mod peek_mut_tests {
+    use super::{Heap, HeapError, HeapProperty, PeekMut};
+
+    #[test]
+    fn peek_mut_on_empty_heap() {
+        let mut heap: Heap<i32> = Heap::new(3);
+
+        assert_eq!(HeapError::Empty, heap.peek_mut().unwrap_err());
+    }
+
+    #[test]
+    fn modify_top_as_min() {
+        let mut heap = Heap::from_vec(vec![5, 3, 9, 8, 1, 6], HeapProperty::Min);
+
+        *heap.peek_mut().unwrap() = 7;
+
+        assert_eq!(vec![3, 5, 6, 8, 7, 9], heap.elements);
+        assert_eq!(3, *heap.peek().unwrap());
+    }
+
+    #[test]
+    fn modify_top_as_max() {
+        let mut heap = Heap::from_vec(vec![5, 3, 9, 8, 1, 6], HeapProperty::Max);
+
+        {
+            let mut top = heap.peek_mut().unwrap();
+            assert_eq!(9, *top);
+            *top -= 8;
+        }
+
+        assert_eq!(vec![8, 6, 5, 3, 1, 1], heap.into_sorted_vec());
+    }
+
+    #[test]
+    fn read_only_access_does_not_sift() {
+        let mut heap = Heap::from_vec(vec![2, 1, 3], HeapProperty::Min);
+        let elements = heap.elements.clone();
+
+        {
+            let top = heap.peek_mut().unwrap();
+            assert_eq!(1, *top);
+        }
+
+        assert_eq!(elements, heap.elements);
+    }
+
+    #[test]
+    fn pop_through_guard() {
+        let mut heap = Heap::new_as(3, HeapProperty::Max);
+        heap.add(4).unwrap();
+        heap.add(6).unwrap();
+        heap.add(5).unwrap();
+
+        let mut top = heap.peek_mut().unwrap();
+        *top = 0;
+
+        assert_eq!(0, PeekMut::pop(top));
+        assert_eq!(2, heap.len());
+        assert_eq!(5, heap.pop().unwrap());
+        assert_eq!(4, heap.pop().unwrap());
+    }
+
+    #[test]
+    fn debug_format() {
+        let mut heap = Heap::new(1);
+        heap.add(7).unwrap();
+
+        assert_eq!("PeekMut(7)", format!("{:?}", heap.peek_mut().unwrap()));
+    }
+}
+
+#[cfg(test)]
+mod push_pop_tests {
+    use super::{Heap, HeapError, HeapProperty};
+
+    fn create_full_heap(property: HeapProperty) -> Heap<i32> {
+        let mut heap = Heap::new_as(6, property);
+        for element in [5, 3, 9, 8, 1, 6] {
+            heap.add(element).unwrap();
+        }
+        heap
+    }
+
+    #[test]
+    fn push_pop_on_empty_heap() {
+        let mut heap = Heap::new(0);
+
+        assert_eq!(4, heap.push_pop(4));
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn push_pop_returns_element_that_comes_first() {
+        let mut heap = create_full_heap(HeapProperty::Min);
+
+        assert_eq!(0, heap.push_pop(0));
+        assert_eq!(1, heap.push_pop(1));
+        assert_eq!(vec![1, 3, 6, 8, 5, 9], heap.elements);
+    }
+
+    #[test]
+    fn push_pop_on_full_heap_as_min() {
+        let mut heap = create_full_heap(HeapProperty::Min);
+
+        assert_eq!(1, heap.push_pop(7));
+        assert!(heap.is_full());
+        assert_eq!(vec![3, 5, 6, 8, 7, 9], heap.elements);
+    }
+
+    #[test]
+    fn push_pop_keeps_top_k_as_min() {
+        let mut heap = Heap::new_as(3, HeapProperty::Min);
+        for element in [4, 1, 7] {
+            heap.add(element).unwrap();
+        }
+        for element in [2, 9, 5, 8, 3] {
+            heap.push_pop(element);
+        }
+
+        assert_eq!(vec![7, 8, 9], heap.into_sorted_vec());
+    }
+
+    #[test]
+    fn replace_on_empty_heap() {
+        let mut heap = Heap::new(1);
+
+        assert_eq!(HeapError::Empty, heap.replace(4).unwrap_err());
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn replace_on_full_heap_as_max() {
+        let mut heap = create_full_heap(HeapProperty::Max);
+
+        assert_eq!(9, heap.replace(10).unwrap());
+        assert_eq!(10, heap.replace(2).unwrap());
+        assert!(heap.is_full());
+        assert_eq!(vec![8, 6, 5, 3, 2, 1], heap.into_sorted_vec());
+    }
+}
+
+#[cfg(test)]
+mod heap_removal_tests {
+    use super::test_util;
+    use super::{Heap, HeapProperty};
+
+    fn create_heap(property: HeapProperty) -> Heap<i32> {
+        Heap::from_vec((1..=10).collect(), property)
+    }
+
+    #[test]
+    fn remove_where_as_min() {
+        let mut heap = create_heap(HeapProperty::Min);
+
+        let mut removed = heap.remove_where(|element| element % 3 == 0);
+        removed.sort_unstable();
+
+        assert_eq!(vec![3, 6, 9], removed);
+        test_util::assert_heap_order(&heap);
+        assert_eq!(vec![1, 2, 4, 5, 7, 8, 10], heap.into_sorted_vec());
+    }
+
+    #[test]
+    fn remove_where_root_as_max() {
+        let mut heap = create_heap(HeapProperty::Max);
+
+        let removed = heap.remove_where(|element| *element == 10);
+
+        assert_eq!(vec![10], removed);
+        test_util::assert_heap_order(&heap);
+        assert_eq!(9, *heap.peek().unwrap());
+    }
+
+    #[test]
+    fn remove_where_nothing_matches() {
+        let mut heap = create_heap(HeapProperty::Min);
+        let elements = heap.elements.clone();
+
+        assert!(heap.remove_where(|element| *element > 10).is_empty());
+        assert_eq!(elements, heap.elements);
+    }
+
+    #[test]
+    fn remove_where_everything_matches() {
+        let mut heap = create_heap(HeapProperty::Min);
+
+        assert_eq!(10, heap.remove_where(|_| true).len());
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn retain_as_max() {
+        let mut heap = create_heap(HeapProperty::Max);
+
+        heap.retain(|element| element % 2 == 1);
+
+        test_util::assert_heap_order(&heap);
+        assert_eq!(vec![9, 7, 5, 3, 1], heap.into_sorted_vec());
+    }
+
+    #[test]
+    fn retain_keeps_bound() {
+        let mut heap = Heap::new(3);
+        heap.extend(vec![3, 1, 2]);
+
+        heap.retain(|element| *element != 1);
+
+        assert_eq!(2, *heap.peek().unwrap());
+        heap.add(0).unwrap();
+        assert!(heap.is_full());
+    }
+}
+
+#[cfg(test)]
+mod heap_meld_tests {
+    use super::test_util;
+    use super::{better_to_rebuild, ByKey, Compare, Heap, HeapError, HeapProperty};
+
+    #[test]
+    fn append_small_heap_one_by_one() {
+        let mut heap = Heap::from_vec((0..100).collect(), HeapProperty::Min);
+        let mut other = Heap::from_vec(vec![-1, 150], HeapProperty::Min);
+
+        assert!(!better_to_rebuild(100, 2));
+        heap.append(&mut other).unwrap();
+
+        test_util::assert_heap_order(&heap);
+        assert!(other.is_empty());
+        assert_eq!(102, heap.len());
+        assert_eq!(-1, *heap.peek().unwrap());
+    }
+
+    #[test]
+    fn append_large_heap_by_rebuilding() {
+        let mut heap = Heap::from_vec(vec![50, 7], HeapProperty::Max);
+        let mut other = Heap::from_vec((0..100).collect(), HeapProperty::Max);
+
+        assert!(better_to_rebuild(2, 100));
+        heap.append(&mut other).unwrap();
+
+        test_util::assert_heap_order(&heap);
+        assert!(other.is_empty());
+        assert_eq!(102, heap.len());
+        assert_eq!(99, *heap.peek().unwrap());
+    }
+
+    #[test]
+    fn append_with_mismatched_properties() {
+        let mut heap = Heap::from_vec(vec![1, 2], HeapProperty::Min);
+        let mut other = Heap::from_vec(vec![3, 4], HeapProperty::Max);
+
+        assert_eq!(
+            HeapError::PropertyMismatch,
+            heap.append(&mut other).unwrap_err()
+        );
+        assert_eq!(2, heap.len());
+        assert_eq!(2, other.len());
+    }
+
+    #[test]
+    fn append_to_bounded_heap_without_room() {
+        let mut heap = Heap::new(3);
+        heap.add(1).unwrap();
+        heap.add(2).unwrap();
+        let mut other = Heap::from_vec(vec![3, 4], HeapProperty::Min);
+
+        assert_eq!(
+            HeapError::Full { capacity: 3 },
+            heap.append(&mut other).unwrap_err()
+        );
+        assert_eq!(2, heap.len());
+        assert_eq!(2, other.len());
+    }
+
+    #[test]
+    fn append_boxed_comparators_with_mismatched_properties() {
+        let comparator1: Box<dyn Compare<i32>> = Box::new(HeapProperty::Min);
+        let comparator2: Box<dyn Compare<i32>> = Box::new(|a: &i32, b: &i32| a.cmp(b));
+        let mut heap = Heap::with_capacity_and_comparator(0, comparator1);
+        let mut other = Heap::with_capacity_and_comparator(0, comparator2);
+
+        assert_eq!(
+            HeapError::PropertyMismatch,
+            heap.append(&mut other).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn merge_heaps() {
+        let heap1 = Heap::from_vec(vec![5, 3, 9], HeapProperty::Max);
+        let heap2 = Heap::from_vec(vec![8, 1, 6], HeapProperty::Max);
+
+        let heap = Heap::merge(heap1, heap2).unwrap();
+
+        assert_eq!(vec![9, 8, 6, 5, 3, 1], heap.into_sorted_vec());
+    }
+
+    #[test]
+    fn merge_heaps_by_key_with_mismatched_properties() {
+        let key = |element: &(u8, char)| element.0;
+        let heap1 = Heap::by_key_bounded(2, key, HeapProperty::Min);
+        let heap2 = Heap::by_key(key, HeapProperty::Max);
+
+        assert_eq!(
+            Some(HeapProperty::Min),
+            ByKey::new(key, HeapProperty::Min).property()
+        );
+        assert_eq!(
+            HeapError::PropertyMismatch,
+            Heap::merge(heap1, heap2).unwrap_err().0
+        );
+    }
+
+    #[test]
+    fn failed_merge_hands_heaps_back() {
+        let heap1 = Heap::from_vec(vec![5, 3, 9], HeapProperty::Min);
+        let heap2 = Heap::from_vec(vec![8, 1, 6], HeapProperty::Max);
+
+        let (error, heap1, heap2) = Heap::merge(heap1, heap2).unwrap_err();
+
+        assert_eq!(HeapError::PropertyMismatch, error);
+        assert_eq!(vec![3, 5, 9], heap1.into_sorted_vec());
+        assert_eq!(vec![8, 6, 1], heap2.into_sorted_vec());
+    }
+
+    #[test]
+    fn failed_merge_into_full_heap_hands_heaps_back() {
+        let mut heap1 = Heap::new(3);
+        heap1.add(2).unwrap();
+        heap1.add(1).unwrap();
+        let heap2 = Heap::from_vec(vec![4, 3], HeapProperty::Min);
+
+        let (error, heap1, heap2) = Heap::merge(heap1, heap2).unwrap_err();
+
+        assert_eq!(HeapError::Full { capacity: 3 }, error);
+        assert!(heap1.is_bounded());
+        assert_eq!(vec![1, 2], heap1.into_sorted_vec());
+        assert_eq!(vec![3, 4], heap2.into_sorted_vec());
+    }
+}
+
+#[cfg(test)]
+mod d_ary_heap_tests {
+    use super::test_util::shuffled;
+    use super::{Heap, HeapError, HeapProperty};
+
+    fn assert_d_ary_heap_order<const D: usize>(heap: &Heap<i32, HeapProperty, D>) {
+        for index in 1..heap.elements.len() {
+            let parent = heap.elements[(index - 1) / D];
+            match heap.comparator {
+                HeapProperty::Min => assert!(parent <= heap.elements[index]),
+                HeapProperty::Max => assert!(parent >= heap.elements[index]),
+            }
+        }
+    }
+
+    #[test]
+    fn add_six_elements_to_ternary_heap() {
+        let mut heap: Heap<i32, HeapProperty, 3> =
+            Heap::bounded_with_comparator(6, HeapProperty::Min);
+        for element in [5, 3, 9, 8, 1, 6] {
+            heap.add(element).unwrap();
+        }
+
+        assert_eq!(vec![1, 3, 9, 8, 5, 6], heap.elements);
+        assert_eq!(HeapError::Full { capacity: 6 }, heap.add(0).unwrap_err());
+    }
+
+    #[test]
+    fn add_and_pop_on_quaternary_heap() {
+        let mut heap: Heap<i32, HeapProperty, 4> =
+            Heap::growable_with_comparator(0, HeapProperty::Max);
+        for element in shuffled(100) {
+            heap.add(element).unwrap();
+            assert_d_ary_heap_order(&heap);
+        }
+
+        for expected in (0..100).rev() {
+            assert_eq!(expected, heap.pop().unwrap());
+            assert_d_ary_heap_order(&heap);
+        }
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn growable_from_vec_of_any_arity() {
+        let heap: Heap<i32, HeapProperty, 5> =
+            Heap::growable_from_vec(shuffled(50), HeapProperty::Min);
+
+        assert_d_ary_heap_order(&heap);
+        assert_eq!((0..50).collect::<Vec<i32>>(), heap.into_sorted_vec());
+    }
+
+    #[test]
+    fn iter_sorted_on_octonary_heap() {
+        let heap: Heap<i32, HeapProperty, 8> =
+            Heap::growable_from_vec(shuffled(30), HeapProperty::Max);
+
+        let elements: Vec<i32> = heap.iter_sorted().copied().collect();
+
+        assert_eq!((0..30).rev().collect::<Vec<i32>>(), elements);
+    }
+
+    #[test]
+    fn remove_and_meld_on_quaternary_heap() {
+        let mut heap: Heap<i32, HeapProperty, 4> =
+            Heap::growable_from_vec(shuffled(40), HeapProperty::Min);
+        let mut other: Heap<i32, HeapProperty, 4> =
+            Heap::growable_from_vec(vec![-3, -1, -2], HeapProperty::Min);
+
+        heap.retain(|element| element % 2 == 0);
+        heap.append(&mut other).unwrap();
+
+        assert_d_ary_heap_order(&heap);
+        assert_eq!(-3, heap.push_pop(10));
+        assert_eq!(-2, heap.replace(-4).unwrap());
+        assert_eq!(-4, *heap.peek().unwrap());
+    }
+
+    #[test]
+    fn binary_heap_is_the_default() {
+        let heap: Heap<i32> = Heap::from_vec(shuffled(20), HeapProperty::Max);
+        let binary_heap: Heap<i32, HeapProperty, 2> =
+            Heap::growable_from_vec(shuffled(20), HeapProperty::Max);
+
+        assert_eq!(binary_heap.elements, heap.elements);
+    }
+}
+
+#[cfg(test)]
+mod typed_heap_tests {
+    use super::{Heap, HeapError, HeapProperty, MaxHeap, MaxOrder, MinHeap, MinOrder, TypedHeap};
+    use std::mem;
+
+    #[test]
+    fn bounded_min_heap() {
+        let mut heap = MinHeap::bounded(6);
+        for element in [5, 3, 9, 8, 1, 6] {
+            heap.add(element).unwrap();
+        }
+
+        assert_eq!(vec![1, 3, 6, 8, 5, 9], heap.elements);
+        assert_eq!(HeapError::Full { capacity: 6 }, heap.add(0).unwrap_err());
+        assert_eq!(
+            vec![1, 3, 5, 6, 8, 9],
+            heap.drain_sorted().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn growable_max_heap() {
+        let mut heap = MaxHeap::growable(0);
+        for element in [5, 3, 9, 8, 1, 6] {
+            heap.add(element).unwrap();
+        }
+
+        assert_eq!(vec![9, 8, 6, 3, 1, 5], heap.elements);
+        assert!(!heap.is_bounded());
+        assert_eq!(
+            vec![9, 8, 6, 5, 3, 1],
+            heap.drain_sorted().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn typed_heaps_have_familiar_constructors() {
+        let mut min_heap = MinHeap::new(3);
+        let mut max_heap = MaxHeap::with_capacity(0);
+        for element in [5, 3, 9] {
+            min_heap.add(element).unwrap();
+            max_heap.add(element).unwrap();
+        }
+
+        assert!(min_heap.is_bounded());
+        assert_eq!(
+            HeapError::Full { capacity: 3 },
+            min_heap.add(1).unwrap_err()
+        );
+        assert!(!max_heap.is_bounded());
+        assert_eq!(9, *max_heap.peek().unwrap());
+    }
+
+    #[test]
+    fn default_typed_heaps_are_empty_and_growable() {
+        let min_heap: MinHeap<i32> = MinHeap::default();
+        let max_heap: Heap<i32, MaxOrder, 4> = Heap::default();
+
+        assert!(min_heap.is_empty());
+        assert!(!min_heap.is_bounded());
+        assert!(max_heap.is_empty());
+        assert!(!max_heap.is_bounded());
+    }
+
+    #[test]
+    fn typed_heaps_from_vec() {
+        let min_heap = MinHeap::from(vec![4, 2, 8]);
+        let max_heap: Heap<i32, MaxOrder, 3> = Heap::from(vec![4, 2, 8]);
+
+        assert_eq!(vec![2, 4, 8], min_heap.into_sorted_vec());
+        assert_eq!(vec![8, 4, 2], max_heap.into_sorted_vec());
+    }
+
+    #[test]
+    fn collect_into_typed_heaps() {
+        let min_heap: MinHeap<i32> = vec![4, 2, 8].into_iter().collect();
+        let max_heap: Heap<i32, MaxOrder, 4> = vec![4, 2, 8].into_iter().collect();
+
+        assert_eq!(2, *min_heap.peek().unwrap());
+        assert_eq!(8, *max_heap.peek().unwrap());
+    }
+
+    #[test]
+    fn typed_heaps_match_runtime_heaps() {
+        let mut runtime_heap = Heap::new_as(6, HeapProperty::Max);
+        let mut typed_heap = MaxHeap::bounded(6);
+        for element in [4, 1, 4, 7, 1, 9] {
+            runtime_heap.add(element).unwrap();
+            typed_heap.add(element).unwrap();
+        }
+
+        assert_eq!(runtime_heap.elements, typed_heap.elements);
+    }
+
+    #[test]
+    fn typed_heaps_meld() {
+        let heap1: MinHeap<i32> = vec![5, 1].into_iter().collect();
+        let heap2: MinHeap<i32> = vec![3, 2].into_iter().collect();
+
+        let heap = Heap::merge(heap1, heap2).unwrap();
+
+        assert_eq!(vec![1, 2, 3, 5], heap.into_sorted_vec());
+    }
+
+    #[test]
+    fn bounded_quaternary_min_heap() {
+        let mut heap = Heap::<i32, MinOrder, 4>::bounded(5);
+        heap.extend(vec![5, 3, 9, 8, 1]);
+
+        assert!(heap.is_full());
+        assert_eq!(vec![1, 3, 5, 8, 9], heap.into_sorted_vec());
+    }
+
+    #[test]
+    fn order_markers_are_zero_sized() {
+        assert_eq!(0, mem::size_of::<MinOrder>());
+        assert_eq!(0, mem::size_of::<MaxOrder>());
+        assert!(mem::size_of::<MinHeap<i32>>() < mem::size_of::<Heap<i32>>());
+    }
+}
+
+#[cfg(test)]
+mod panic_safety_tests {
+    use super::{Compare, Heap};
+    use std::cell::Cell;
+    use std::cmp::Ordering;
+    use std::panic::{self, AssertUnwindSafe};
+
+    struct Counted<'a> {
+        value: i32,
+        drops: &'a Cell<usize>,
+    }
+
+    impl<'a> Drop for Counted<'a> {
+        fn drop(&mut self) {
+            self.drops.set(self.drops.get() + 1);
+        }
+    }
+
+    fn counted<'a>(values: &[i32], drops: &'a Cell<usize>) -> Vec<Counted<'a>> {
+        values
+            .iter()
+            .map(|&value| Counted { value, drops })
+            .collect()
+    }
+
+    /// Orders by value and panics once `budget` comparisons have been made.
+    fn panicking_order(
+        budget: &Cell<usize>,
+    ) -> impl Fn(&Counted, &Counted) -> Ordering + Copy + '_ {
+        move |a, b| {
+            if budget.get() == 0 {
+                panic!("comparison budget exhausted");
+            }
+            budget.set(budget.get() - 1);
+            a.value.cmp(&b.value)
+        }
+    }
+
+    fn values<'a, C: Compare<Counted<'a>>>(heap: &Heap<Counted<'a>, C>) -> Vec<i32> {
+        let mut values: Vec<i32> = heap.iter().map(|element| element.value).collect();
+        values.sort_unstable();
+        values
+    }
+
+    #[test]
+    fn add_keeps_every_element_when_comparator_panics() {
+        let drops = Cell::new(0);
+        let budget = Cell::new(usize::MAX);
+        let mut heap = Heap::from_vec_with_comparator(
+            counted(&[1, 2, 3, 4, 5, 6, 7], &drops),
+            panicking_order(&budget),
+        );
+
+        budget.set(1);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            heap.add(Counted {
+                value: 0,
+                drops: &drops,
+            })
+            .unwrap();
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(0, drops.get());
+        assert_eq!(vec![0, 1, 2, 3, 4, 5, 6, 7], values(&heap));
+
+        budget.set(usize::MAX);
+        heap.rebuild();
+        heap.add(Counted {
+            value: 8,
+            drops: &drops,
+        })
+        .unwrap();
+        drop(heap);
+
+        assert_eq!(9, drops.get());
+    }
+
+    #[test]
+    fn pop_drops_the_popped_element_once_when_comparator_panics() {
+        let drops = Cell::new(0);
+        let budget = Cell::new(usize::MAX);
+        let mut heap = Heap::from_vec_with_comparator(
+            counted(&[1, 2, 3, 4, 5, 6, 7], &drops),
+            panicking_order(&budget),
+        );
+
+        budget.set(1);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            heap.pop().unwrap();
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(1, drops.get());
+        assert_eq!(vec![2, 3, 4, 5, 6, 7], values(&heap));
+
+        budget.set(usize::MAX);
+        heap.rebuild();
+        assert_eq!(2, heap.pop().unwrap().value);
+        drop(heap);
+
+        assert_eq!(7, drops.get());
+    }
+
+    #[test]
+    fn heapify_drops_every_element_once_when_comparator_panics() {
+        let drops = Cell::new(0);
+        let budget = Cell::new(3);
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            Heap::from_vec_with_comparator(
+                counted(&[7, 6, 5, 4, 3, 2, 1], &drops),
+                panicking_order(&budget),
+            )
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(7, drops.get());
+    }
+
+    #[test]
+    fn append_keeps_every_element_when_comparator_panics() {
+        for (size, other_size) in [(7, 1), (1, 7)] {
+            let drops = Cell::new(0);
+            let budget = Cell::new(usize::MAX);
+            let mut heap = Heap::from_vec_with_comparator(
+                counted(&(1..=size).collect::<Vec<_>>(), &drops),
+                panicking_order(&budget),
+            );
+            let mut other = Heap::from_vec_with_comparator(
+                counted(&(-other_size..0).collect::<Vec<_>>(), &drops),
+                panicking_order(&budget),
+            );
+
+            budget.set(1);
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                heap.append(&mut other).unwrap();
+            }));
+
+            assert!(result.is_err());
+            assert_eq!(0, drops.get());
+            assert_eq!(8, heap.len() + other.len());
+
+            budget.set(usize::MAX);
+            drop(heap);
+            drop(other);
+
+            assert_eq!(8, drops.get());
+        }
+    }
+
+    #[test]
+    fn merge_drops_every_element_once_when_comparator_panics() {
+        let drops = Cell::new(0);
+        let budget = Cell::new(usize::MAX);
+        let heap1 = Heap::from_vec_with_comparator(
+            counted(&[1, 2, 3, 4], &drops),
+            panicking_order(&budget),
+        );
+        let heap2 = Heap::from_vec_with_comparator(
+            counted(&[-3, -2, -1], &drops),
+            panicking_order(&budget),
+        );
+
+        budget.set(1);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| Heap::merge(heap1, heap2)));
+
+        assert!(result.is_err());
+        assert_eq!(7, drops.get());
+    }
+}
+
+#[cfg(test)]
+mod heap_invariant_tests {
+    use super::{Heap, HeapProperty};
+    use std::cell::Cell;
+
+    fn cell_heap(
+        values: &[i32],
+    ) -> Heap<Cell<i32>, impl Fn(&Cell<i32>, &Cell<i32>) -> std::cmp::Ordering> {
+        Heap::from_vec_with_comparator(
+            values.iter().copied().map(Cell::new).collect(),
+            |a: &Cell<i32>, b: &Cell<i32>| a.get().cmp(&b.get()),
+        )
+    }
+
+    #[test]
+    fn empty_heap_is_valid() {
+        let heap: Heap<i32> = Heap::with_capacity(0);
+
+        assert!(heap.is_valid());
+        assert_eq!(None, heap.check_invariant());
+    }
+
+    #[test]
+    fn heap_built_from_vec_is_valid() {
+        let heap = Heap::from_vec(vec![5, 3, 8, 1, 9, 2, 7], HeapProperty::Max);
+
+        assert!(heap.is_valid());
+    }
+
+    #[test]
+    fn check_invariant_reports_first_violation() {
+        let heap = cell_heap(&[1, 2, 3, 4, 5, 6, 7]);
+
+        heap.iter().nth(4).unwrap().set(0);
+
+        assert!(!heap.is_valid());
+        assert_eq!(Some((1, 4)), heap.check_invariant());
+    }
+
+    #[test]
+    fn check_invariant_follows_arity() {
+        let mut heap: Heap<i32, HeapProperty, 3> =
+            Heap::growable_with_comparator(0, HeapProperty::Min);
+        for element in (1..=10).rev() {
+            heap.add(element).unwrap();
+        }
+
+        assert!(heap.is_valid());
+
+        heap.elements.swap(1, 4);
+
+        assert_eq!(Some((1, 4)), heap.check_invariant());
+    }
+
+    #[cfg(all(debug_assertions, feature = "check-invariants"))]
+    #[test]
+    #[should_panic(expected = "Violated heap property between parent 0 and child 1.")]
+    fn add_asserts_invariant() {
+        let mut heap = cell_heap(&[1, 2, 3]);
+
+        heap.iter().nth(1).unwrap().set(0);
+        heap.add(Cell::new(4)).unwrap();
+    }
+
+    #[test]
+    fn rebuild_restores_heap_order() {
+        let mut heap = cell_heap(&[1, 2, 3, 4, 5, 6, 7]);
+        for element in heap.iter() {
+            element.set(10 - element.get());
+        }
+
+        assert!(!heap.is_valid());
+
+        heap.rebuild();
+
+        assert!(heap.is_valid());
+        assert_eq!(3, heap.pop().unwrap().get());
+    }
+}
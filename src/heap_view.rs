@@ -0,0 +1,243 @@
+use core::fmt;
+use core::slice;
+
+use super::{heapify, sift_down, sift_up, Compare, HeapError, HeapProperty};
+
+/// A binary heap over a buffer owned by the caller, such as a slice of a
+/// larger array. The heap holds at most as many elements as the buffer and
+/// never allocates. Its elements occupy the front of the buffer, and the rest
+/// of the buffer is left as it was until `add` overwrites it.
+pub struct HeapView<'a, T, C = HeapProperty> {
+    buffer: &'a mut [T],
+    size: usize,
+    comparator: C,
+}
+
+impl<'a, T: Ord> HeapView<'a, T> {
+    /// Creates an empty min heap over `buffer`.
+    pub fn new(buffer: &'a mut [T]) -> Self {
+        HeapView::with_comparator(buffer, HeapProperty::Min)
+    }
+
+    pub fn new_as(buffer: &'a mut [T], property: HeapProperty) -> Self {
+        HeapView::with_comparator(buffer, property)
+    }
+
+    /// Creates a full heap out of all elements of `buffer` in O(n) time.
+    pub fn from_slice(buffer: &'a mut [T], property: HeapProperty) -> Self {
+        HeapView::from_slice_with_comparator(buffer, property)
+    }
+}
+
+impl<'a, T, C: Compare<T>> HeapView<'a, T, C> {
+    pub fn with_comparator(buffer: &'a mut [T], comparator: C) -> Self {
+        HeapView {
+            buffer,
+            size: 0,
+            comparator,
+        }
+    }
+
+    pub fn from_slice_with_comparator(buffer: &'a mut [T], comparator: C) -> Self {
+        heapify(buffer, &comparator);
+
+        HeapView {
+            size: buffer.len(),
+            buffer,
+            comparator,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.buffer.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.size == self.buffer.len()
+    }
+
+    /// Empties the heap. The elements stay in the buffer until `add`
+    /// overwrites them.
+    pub fn clear(&mut self) {
+        self.size = 0;
+    }
+
+    /// Returns an iterator over the elements in the heap in arbitrary order.
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.buffer[..self.size].iter()
+    }
+
+    pub fn peek(&self) -> Result<&T, HeapError> {
+        self.validate_non_empty_heap()?;
+
+        Ok(&self.buffer[0])
+    }
+
+    /// Removes the top of the heap. The removed element stays in the buffer
+    /// right after the remaining elements, so a reference to it is returned.
+    pub fn pop(&mut self) -> Result<&mut T, HeapError> {
+        self.validate_non_empty_heap()?;
+
+        self.size -= 1;
+        self.buffer.swap(0, self.size);
+
+        sift_down(&mut self.buffer[..self.size], 0, &self.comparator);
+
+        Ok(&mut self.buffer[self.size])
+    }
+
+    /// Adds `element`, dropping the value that occupied its slot in the
+    /// buffer.
+    pub fn add(&mut self, element: T) -> Result<(), HeapError> {
+        if self.is_full() {
+            return Err(HeapError::Full {
+                capacity: self.buffer.len(),
+            });
+        }
+
+        self.buffer[self.size] = element;
+        self.size += 1;
+
+        sift_up(
+            &mut self.buffer[..self.size],
+            self.size - 1,
+            &self.comparator,
+        );
+
+        Ok(())
+    }
+
+    /// Gives the buffer back. Its first `len` elements are in heap order.
+    pub fn into_slice(self) -> &'a mut [T] {
+        self.buffer
+    }
+
+    fn validate_non_empty_heap(&self) -> Result<(), HeapError> {
+        if self.is_empty() {
+            return Err(HeapError::Empty);
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a, T: fmt::Debug, C> fmt::Debug for HeapView<'a, T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list()
+            .entries(self.buffer[..self.size].iter())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod heap_view_tests {
+    use super::HeapView;
+    use crate::{HeapError, HeapProperty};
+
+    #[test]
+    fn peek_and_pop_on_empty_heap() {
+        let mut buffer = [0; 3];
+        let mut heap = HeapView::new(&mut buffer);
+
+        assert_eq!(HeapError::Empty, heap.peek().unwrap_err());
+        assert_eq!(HeapError::Empty, heap.pop().unwrap_err());
+    }
+
+    #[test]
+    fn add_to_full_heap() {
+        let mut buffer = [0; 2];
+        let mut heap = HeapView::new(&mut buffer);
+
+        heap.add(2).unwrap();
+        heap.add(1).unwrap();
+
+        assert!(heap.is_full());
+        assert_eq!(HeapError::Full { capacity: 2 }, heap.add(3).unwrap_err());
+    }
+
+    #[test]
+    fn add_and_pop_as_max() {
+        let mut buffer = [0; 5];
+        let mut heap = HeapView::new_as(&mut buffer, HeapProperty::Max);
+        for element in [3, 5, 1, 4, 2] {
+            heap.add(element).unwrap();
+        }
+
+        assert_eq!(Ok(&5), heap.peek());
+
+        let mut popped = Vec::new();
+        while let Ok(element) = heap.pop() {
+            popped.push(*element);
+        }
+
+        assert_eq!(vec![5, 4, 3, 2, 1], popped);
+    }
+
+    #[test]
+    fn pop_leaves_element_after_remaining_ones() {
+        let mut buffer = [0; 4];
+        let mut heap = HeapView::new(&mut buffer);
+        for element in [3, 1, 2] {
+            heap.add(element).unwrap();
+        }
+
+        assert_eq!(Ok(&mut 1), heap.pop());
+        assert_eq!(2, heap.len());
+
+        let buffer = heap.into_slice();
+
+        assert_eq!(1, buffer[2]);
+    }
+
+    #[test]
+    fn view_over_part_of_array() {
+        let mut buffer = [9, 9, 0, 0, 0, 9];
+        let mut heap = HeapView::new(&mut buffer[2..5]);
+        for element in [7, 6, 8] {
+            heap.add(element).unwrap();
+        }
+
+        assert_eq!(3, heap.capacity());
+        assert_eq!("[6, 7, 8]", format!("{:?}", heap));
+
+        heap.into_slice();
+
+        assert_eq!([9, 9, 6, 7, 8, 9], buffer);
+    }
+
+    #[test]
+    fn from_slice_heapifies_buffer() {
+        let mut buffer = ["pear", "fig", "apple", "kiwi"];
+        let mut heap = HeapView::from_slice(&mut buffer, HeapProperty::Min);
+
+        assert!(heap.is_full());
+        assert_eq!(Ok(&mut "apple"), heap.pop());
+        assert_eq!(Ok(&mut "fig"), heap.pop());
+
+        heap.clear();
+
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn add_and_pop_with_comparator() {
+        let mut buffer = [""; 3];
+        let mut heap =
+            HeapView::with_comparator(&mut buffer, |a: &&str, b: &&str| a.len().cmp(&b.len()));
+        for element in ["three", "one", "four"] {
+            heap.add(element).unwrap();
+        }
+
+        assert_eq!(Ok(&"one"), heap.peek());
+        assert_eq!(Ok(&mut "one"), heap.pop());
+        assert_eq!(Ok(&mut "four"), heap.pop());
+    }
+}
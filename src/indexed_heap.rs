@@ -0,0 +1,432 @@
+use alloc::vec::Vec;
+use core::fmt;
+use core::mem;
+use core::sync::atomic::{self, AtomicUsize};
+
+use super::{
+    get_left_child_index, get_parent_index, get_right_child_index, is_root, precedes, Compare,
+    HeapError, HeapProperty,
+};
+
+/// Identifies an element added to an `IndexedHeap` for as long as it stays
+/// in the heap. Once the element leaves, its slot is reused for a later
+/// element, and the slot's generation tells the old handle apart. Handles
+/// also remember the heap that issued them, so other heaps reject them.
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Handle {
+    heap_id: usize,
+    slot: usize,
+    generation: usize,
+}
+
+impl fmt::Debug for Handle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Handle")
+            .field(&self.slot)
+            .field(&self.generation)
+            .finish()
+    }
+}
+
+static NEXT_HEAP_ID: AtomicUsize = AtomicUsize::new(0);
+
+fn next_heap_id() -> usize {
+    NEXT_HEAP_ID.fetch_add(1, atomic::Ordering::Relaxed)
+}
+
+struct Slot {
+    position: Option<usize>,
+    generation: usize,
+}
+
+/// A heap that hands out a `Handle` for every added element, so that the
+/// element can later be looked up, re-prioritized or removed.
+pub struct IndexedHeap<T, C = HeapProperty> {
+    id: usize,
+    elements: Vec<(Handle, T)>,
+    slots: Vec<Slot>,
+    free_slots: Vec<usize>,
+    bound: Option<usize>,
+    comparator: C,
+}
+
+impl<T: Ord> IndexedHeap<T> {
+    pub fn new(size: usize) -> Self {
+        IndexedHeap::with_comparator(size, HeapProperty::Min)
+    }
+
+    pub fn new_as(size: usize, property: HeapProperty) -> Self {
+        IndexedHeap::with_comparator(size, property)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        IndexedHeap::with_capacity_and_comparator(capacity, HeapProperty::Min)
+    }
+
+    pub fn with_capacity_as(capacity: usize, property: HeapProperty) -> Self {
+        IndexedHeap::with_capacity_and_comparator(capacity, property)
+    }
+}
+
+impl<T, C: Compare<T>> IndexedHeap<T, C> {
+    pub fn with_comparator(size: usize, comparator: C) -> Self {
+        IndexedHeap {
+            id: next_heap_id(),
+            elements: Vec::with_capacity(size),
+            slots: Vec::new(),
+            free_slots: Vec::new(),
+            bound: Some(size),
+            comparator,
+        }
+    }
+
+    pub fn with_capacity_and_comparator(capacity: usize, comparator: C) -> Self {
+        IndexedHeap {
+            id: next_heap_id(),
+            elements: Vec::with_capacity(capacity),
+            slots: Vec::new(),
+            free_slots: Vec::new(),
+            bound: None,
+            comparator,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        Some(self.elements.len()) == self.bound
+    }
+
+    pub fn contains(&self, handle: Handle) -> bool {
+        self.get_position(handle).is_some()
+    }
+
+    pub fn get(&self, handle: Handle) -> Option<&T> {
+        self.get_position(handle)
+            .map(|index| &self.elements[index].1)
+    }
+
+    pub fn peek(&self) -> Result<(Handle, &T), HeapError> {
+        self.validate_non_empty_heap()?;
+
+        let (handle, element) = &self.elements[0];
+        Ok((*handle, element))
+    }
+
+    pub fn pop(&mut self) -> Result<(Handle, T), HeapError> {
+        self.validate_non_empty_heap()?;
+
+        let result = self.remove_at(0);
+
+        Ok(result)
+    }
+
+    pub fn add(&mut self, element: T) -> Result<Handle, HeapError> {
+        if let Some(capacity) = self.bound {
+            if self.elements.len() == capacity {
+                return Err(HeapError::Full { capacity });
+            }
+        }
+
+        let slot = self.free_slots.pop().unwrap_or_else(|| {
+            self.slots.push(Slot {
+                position: None,
+                generation: 0,
+            });
+            self.slots.len() - 1
+        });
+        self.slots[slot].position = Some(self.elements.len());
+
+        let handle = Handle {
+            heap_id: self.id,
+            slot,
+            generation: self.slots[slot].generation,
+        };
+        self.elements.push((handle, element));
+
+        self.recalculate_up(self.elements.len() - 1);
+
+        Ok(handle)
+    }
+
+    /// Replaces the element behind `handle` with `element`, moving it up or
+    /// down as needed, and returns the previous element.
+    pub fn change_priority(&mut self, handle: Handle, element: T) -> Result<T, HeapError> {
+        let index = self.get_position(handle).ok_or(HeapError::UnknownHandle)?;
+
+        let moves_up = precedes(&self.comparator, &element, &self.elements[index].1);
+        let result = mem::replace(&mut self.elements[index].1, element);
+
+        if moves_up {
+            self.recalculate_up(index);
+        } else {
+            self.recalculate_down(index);
+        }
+
+        Ok(result)
+    }
+
+    pub fn remove(&mut self, handle: Handle) -> Result<T, HeapError> {
+        let index = self.get_position(handle).ok_or(HeapError::UnknownHandle)?;
+
+        let (_, result) = self.remove_at(index);
+
+        Ok(result)
+    }
+
+    fn validate_non_empty_heap(&self) -> Result<(), HeapError> {
+        if self.elements.is_empty() {
+            return Err(HeapError::Empty);
+        }
+
+        Ok(())
+    }
+
+    fn get_position(&self, handle: Handle) -> Option<usize> {
+        if handle.heap_id != self.id {
+            return None;
+        }
+
+        self.slots
+            .get(handle.slot)
+            .filter(|slot| slot.generation == handle.generation)
+            .and_then(|slot| slot.position)
+    }
+
+    fn set_position(&mut self, index: usize) {
+        let slot = self.elements[index].0.slot;
+        self.slots[slot].position = Some(index);
+    }
+
+    fn remove_at(&mut self, index: usize) -> (Handle, T) {
+        let last_index = self.elements.len() - 1;
+        self.swap(index, last_index);
+
+        let (handle, element) = self.elements.pop().unwrap();
+        let slot = &mut self.slots[handle.slot];
+        slot.position = None;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free_slots.push(handle.slot);
+
+        if index < self.elements.len() {
+            self.recalculate_up(index);
+            self.recalculate_down(index);
+        }
+
+        (handle, element)
+    }
+
+    fn swap(&mut self, index1: usize, index2: usize) {
+        self.elements.swap(index1, index2);
+        self.set_position(index1);
+        self.set_position(index2);
+    }
+
+    fn precedes_at(&self, index1: usize, index2: usize) -> bool {
+        precedes(
+            &self.comparator,
+            &self.elements[index1].1,
+            &self.elements[index2].1,
+        )
+    }
+
+    fn recalculate_down(&mut self, mut index: usize) {
+        while get_left_child_index(index) < self.elements.len() {
+            let left_child_index = get_left_child_index(index);
+            let right_child_index = get_right_child_index(index);
+            let candidate_index = if right_child_index < self.elements.len()
+                && self.precedes_at(right_child_index, left_child_index)
+            {
+                right_child_index
+            } else {
+                left_child_index
+            };
+
+            if !self.precedes_at(candidate_index, index) {
+                break;
+            }
+
+            self.swap(candidate_index, index);
+            index = candidate_index;
+        }
+    }
+
+    fn recalculate_up(&mut self, mut index: usize) {
+        while !is_root(index) && self.precedes_at(index, get_parent_index::<2>(index)) {
+            let parent_index = get_parent_index::<2>(index);
+            self.swap(parent_index, index);
+            index = parent_index;
+        }
+    }
+}
+
+impl<T: fmt::Debug, C> fmt::Debug for IndexedHeap<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map()
+            .entries(
+                self.elements
+                    .iter()
+                    .map(|(handle, element)| (handle, element)),
+            )
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod indexed_heap_tests {
+    use super::{Handle, IndexedHeap};
+    use crate::{HeapError, HeapProperty};
+
+    fn assert_positions(heap: &IndexedHeap<i32>) {
+        for (index, (handle, _)) in heap.elements.iter().enumerate() {
+            assert_eq!(Some(index), heap.slots[handle.slot].position);
+        }
+    }
+
+    fn drain(heap: &mut IndexedHeap<i32>) -> Vec<i32> {
+        let mut elements = Vec::new();
+        while let Ok((_, element)) = heap.pop() {
+            elements.push(element);
+        }
+        elements
+    }
+
+    #[test]
+    fn add_and_pop_as_min() {
+        let mut heap = IndexedHeap::new_as(6, HeapProperty::Min);
+        let handles: Vec<Handle> = [5, 3, 9, 8, 1, 6]
+            .iter()
+            .map(|element| heap.add(*element).unwrap())
+            .collect();
+
+        assert_positions(&heap);
+        assert_eq!((handles[4], &1), heap.peek().unwrap());
+        assert_eq!(HeapError::Full { capacity: 6 }, heap.add(0).unwrap_err());
+        assert_eq!((handles[4], 1), heap.pop().unwrap());
+        assert!(!heap.contains(handles[4]));
+        assert_positions(&heap);
+        assert_eq!(vec![3, 5, 6, 8, 9], drain(&mut heap));
+        assert_eq!(HeapError::Empty, heap.pop().unwrap_err());
+    }
+
+    #[test]
+    fn change_priority_up_and_down() {
+        let mut heap = IndexedHeap::with_capacity_as(0, HeapProperty::Min);
+        let handles: Vec<Handle> = [5, 3, 9, 8, 1, 6]
+            .iter()
+            .map(|element| heap.add(*element).unwrap())
+            .collect();
+
+        assert_eq!(9, heap.change_priority(handles[2], 0).unwrap());
+        assert_positions(&heap);
+        assert_eq!((handles[2], &0), heap.peek().unwrap());
+
+        assert_eq!(0, heap.change_priority(handles[2], 7).unwrap());
+        assert_positions(&heap);
+        assert_eq!(Some(&7), heap.get(handles[2]));
+        assert_eq!(vec![1, 3, 5, 6, 7, 8], drain(&mut heap));
+    }
+
+    #[test]
+    fn remove_by_handle() {
+        let mut heap = IndexedHeap::new_as(6, HeapProperty::Max);
+        let handles: Vec<Handle> = [5, 3, 9, 8, 1, 6]
+            .iter()
+            .map(|element| heap.add(*element).unwrap())
+            .collect();
+
+        assert_eq!(3, heap.remove(handles[1]).unwrap());
+        assert_eq!(9, heap.remove(handles[2]).unwrap());
+        assert_positions(&heap);
+        assert_eq!(4, heap.len());
+        assert!(heap.contains(handles[0]));
+        assert!(!heap.contains(handles[1]));
+        assert_eq!(
+            HeapError::UnknownHandle,
+            heap.remove(handles[1]).unwrap_err()
+        );
+        assert_eq!(
+            HeapError::UnknownHandle,
+            heap.change_priority(handles[2], 4).unwrap_err()
+        );
+        assert_eq!(vec![8, 6, 5, 1], drain(&mut heap));
+    }
+
+    #[test]
+    fn remove_last_element() {
+        let mut heap = IndexedHeap::new(2);
+        heap.add(1).unwrap();
+        let handle = heap.add(2).unwrap();
+
+        assert_eq!(2, heap.remove(handle).unwrap());
+        assert_positions(&heap);
+        assert_eq!(None, heap.get(handle));
+    }
+
+    #[test]
+    fn foreign_handle_is_unknown() {
+        let mut other = IndexedHeap::new(3);
+        other.add(1).unwrap();
+        let handle = other.add(2).unwrap();
+        let mut heap = IndexedHeap::new(3);
+        heap.add(3).unwrap();
+        heap.add(4).unwrap();
+
+        assert!(!heap.contains(handle));
+        assert_eq!(None, heap.get(handle));
+        assert_eq!(
+            HeapError::UnknownHandle,
+            heap.change_priority(handle, 0).unwrap_err()
+        );
+        assert_eq!(HeapError::UnknownHandle, heap.remove(handle).unwrap_err());
+        assert_eq!(vec![3, 4], drain(&mut heap));
+    }
+
+    #[test]
+    fn debug_lists_handles_and_elements() {
+        let mut heap = IndexedHeap::new(2);
+        heap.add(2).unwrap();
+        heap.add(1).unwrap();
+
+        assert_eq!("{Handle(1, 0): 1, Handle(0, 0): 2}", format!("{:?}", heap));
+    }
+
+    #[test]
+    fn reuses_slots_of_removed_elements() {
+        let mut heap = IndexedHeap::new(3);
+        for round in 0..100 {
+            heap.add(round).unwrap();
+            let handle = heap.add(round + 1).unwrap();
+            assert_eq!(round, heap.pop().unwrap().1);
+            assert_eq!(round + 1, heap.remove(handle).unwrap());
+        }
+
+        assert!(heap.is_empty());
+        assert_eq!(2, heap.slots.len());
+        assert_eq!(2, heap.free_slots.len());
+    }
+
+    #[test]
+    fn stale_handle_is_unknown_after_slot_reuse() {
+        let mut heap = IndexedHeap::new(2);
+        let stale = heap.add(5).unwrap();
+        heap.remove(stale).unwrap();
+        let fresh = heap.add(7).unwrap();
+
+        assert_ne!(stale, fresh);
+        assert!(!heap.contains(stale));
+        assert_eq!(None, heap.get(stale));
+        assert_eq!(
+            HeapError::UnknownHandle,
+            heap.change_priority(stale, 1).unwrap_err()
+        );
+        assert_eq!(HeapError::UnknownHandle, heap.remove(stale).unwrap_err());
+        assert_eq!(Some(&7), heap.get(fresh));
+    }
+}
@@ -1,653 +1,575 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[cfg(not(heap_in_rust_no_alloc))]
+extern crate alloc;
+
+#[cfg(not(heap_in_rust_no_alloc))]
+use alloc::boxed::Box;
+#[cfg(not(heap_in_rust_no_alloc))]
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+use core::mem::ManuallyDrop;
+use core::ptr;
+
+mod array_heap;
+#[cfg(not(heap_in_rust_no_alloc))]
+mod heap;
+mod heap_view;
+#[cfg(not(heap_in_rust_no_alloc))]
+mod indexed_heap;
+#[cfg(not(heap_in_rust_no_alloc))]
+mod min_max_heap;
+
+pub use array_heap::ArrayHeap;
+#[cfg(not(heap_in_rust_no_alloc))]
+pub use heap::{DrainSorted, Heap, IterSorted, MaxHeap, MinHeap, PeekMut, TypedHeap};
+pub use heap_view::HeapView;
+#[cfg(not(heap_in_rust_no_alloc))]
+pub use indexed_heap::{Handle, IndexedHeap};
+#[cfg(not(heap_in_rust_no_alloc))]
+pub use min_max_heap::MinMaxHeap;
+
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum HeapProperty {
     Min,
     Max,
 }
 
-pub struct Heap {
-    elements: Vec<i32>,
-    size: usize,
-    property: HeapProperty,
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HeapError {
+    Empty,
+    Full { capacity: usize },
+    UnknownHandle,
+    PropertyMismatch,
 }
 
-impl Heap {
-    pub fn new(size: usize) -> Self {
-        Heap {
-            elements: vec![0; size],
-            size: 0,
-            property: HeapProperty::Min,
+impl fmt::Display for HeapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HeapError::Empty => write!(f, "Empty heap."),
+            HeapError::Full { .. } => write!(f, "Full heap."),
+            HeapError::UnknownHandle => write!(f, "Unknown handle."),
+            HeapError::PropertyMismatch => write!(f, "Mismatched heap properties."),
         }
     }
+}
 
-    pub fn new_as(size: usize, property: HeapProperty) -> Self {
-        Heap {
-            elements: vec![0; size],
-            size: 0,
-            property,
-        }
-    }
-
-    pub fn is_empty(&self) -> bool {
-        self.size == 0
-    }
-
-    pub fn peek(&self) -> Result<i32, String> {
-        self.validate_non_empty_heap()?;
-
-        Ok(self.elements[0])
-    }
-
-    pub fn pop(&mut self) -> Result<i32, String> {
-        self.validate_non_empty_heap()?;
-
-        let result = self.elements[0];
-        self.elements[0] = self.elements[(self.size - 1)];
-        self.size -= 1;
-
-        self.recalculate_down();
-
-        Ok(result)
-    }
-
-    pub fn add(&mut self, element: i32) -> Result<(), String> {
-        if self.size == self.elements.len() {
-            return Err("Full heap.".to_string());
-        }
-
-        self.elements[self.size] = element;
-        self.size += 1;
+#[cfg(feature = "std")]
+impl std::error::Error for HeapError {}
 
-        self.recalculate_up();
+/// Decides the order of heap elements: an element that compares as
+/// `Ordering::Less` than another one is closer to the top of the heap.
+pub trait Compare<T: ?Sized> {
+    fn compare(&self, a: &T, b: &T) -> Ordering;
 
-        Ok(())
+    /// Returns the direction the comparator orders in, if it is known. Heaps
+    /// whose comparators report different directions can't be melded.
+    fn property(&self) -> Option<HeapProperty> {
+        None
     }
+}
 
-    fn validate_non_empty_heap(&self) -> Result<(), String> {
-        if self.size == 0 {
-            return Err("Empty heap.".to_string());
+impl<T: Ord + ?Sized> Compare<T> for HeapProperty {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        match self {
+            HeapProperty::Min => a.cmp(b),
+            HeapProperty::Max => b.cmp(a),
         }
-
-        Ok(())
     }
 
-    fn get_parent_index(&self, index: usize) -> usize {
-        (index - 1) / 2
+    fn property(&self) -> Option<HeapProperty> {
+        Some(*self)
     }
+}
 
-    fn get_left_child_index(&self, index: usize) -> usize {
-        2 * index + 1
+impl<T: ?Sized, F> Compare<T> for F
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        self(a, b)
     }
+}
 
-    fn get_right_child_index(&self, index: usize) -> usize {
-        2 * index + 2
+#[cfg(not(heap_in_rust_no_alloc))]
+impl<T: ?Sized> Compare<T> for Box<dyn Compare<T> + '_> {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        (**self).compare(a, b)
     }
 
-    fn is_root(&self, index: usize) -> bool {
-        index == 0
+    fn property(&self) -> Option<HeapProperty> {
+        (**self).property()
     }
+}
 
-    fn has_left_child(&self, index: usize) -> bool {
-        self.get_left_child_index(index) < self.size
+impl<T: ?Sized> Compare<T> for &dyn Compare<T> {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        (**self).compare(a, b)
     }
 
-    fn has_right_child(&self, index: usize) -> bool {
-        self.get_right_child_index(index) < self.size
+    fn property(&self) -> Option<HeapProperty> {
+        (**self).property()
     }
+}
 
-    fn get_parent(&self, index: usize) -> i32 {
-        self.elements[self.get_parent_index(index)]
-    }
+/// Orders elements with the smallest on top. As a zero-sized type parameter it
+/// fixes the direction of a `MinHeap` at compile time.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct MinOrder;
 
-    fn get_left_child(&self, index: usize) -> i32 {
-        self.elements[self.get_left_child_index(index)]
-    }
+/// Orders elements with the largest on top. As a zero-sized type parameter it
+/// fixes the direction of a `MaxHeap` at compile time.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct MaxOrder;
 
-    fn get_right_child(&self, index: usize) -> i32 {
-        self.elements[self.get_right_child_index(index)]
+impl<T: Ord + ?Sized> Compare<T> for MinOrder {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        a.cmp(b)
     }
 
-    fn swap(&mut self, index1: usize, index2: usize) {
-        self.elements.swap(index1, index2);
+    fn property(&self) -> Option<HeapProperty> {
+        Some(HeapProperty::Min)
     }
+}
 
-    fn recalculate_down(&mut self) {
-        let mut index = 0_usize;
-        while self.has_left_child(index) {
-            let candidate_index = if self.has_right_child(index)
-                && match self.property {
-                    HeapProperty::Min => self.get_right_child(index) < self.get_left_child(index),
-                    HeapProperty::Max => self.get_right_child(index) > self.get_left_child(index),
-                } {
-                self.get_right_child_index(index)
-            } else {
-                self.get_left_child_index(index)
-            };
-
-            if match self.property {
-                HeapProperty::Min => self.elements[candidate_index] >= self.elements[index],
-                HeapProperty::Max => self.elements[candidate_index] < self.elements[index],
-            } {
-                break;
-            }
-
-            self.swap(candidate_index, index);
-            index = candidate_index;
-        }
+impl<T: Ord + ?Sized> Compare<T> for MaxOrder {
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        b.cmp(a)
     }
 
-    fn recalculate_up(&mut self) {
-        let mut index = self.size - 1;
-        while !self.is_root(index)
-            && match self.property {
-                HeapProperty::Min => self.elements[index] < self.get_parent(index),
-                HeapProperty::Max => self.elements[index] > self.get_parent(index),
-            }
-        {
-            let parent_index = self.get_parent_index(index);
-            self.swap(parent_index, index);
-            index = parent_index;
-        }
+    fn property(&self) -> Option<HeapProperty> {
+        Some(HeapProperty::Max)
     }
 }
 
-#[cfg(test)]
-mod heap_constructor_tests {
-    use super::{Heap, HeapProperty};
-
-    fn create_heap(size: usize) -> Heap {
-        Heap::new(size)
-    }
+/// Orders elements by the key that `key` extracts from them, in the direction
+/// given by `property`.
+#[derive(Debug, Copy, Clone)]
+pub struct ByKey<F> {
+    key: F,
+    property: HeapProperty,
+}
 
-    fn create_heap_as(size: usize, property: HeapProperty) -> Heap {
-        Heap::new_as(size, property)
+impl<F> ByKey<F> {
+    pub fn new(key: F, property: HeapProperty) -> Self {
+        ByKey { key, property }
     }
+}
 
-    fn assert_constructor(heap: &Heap, size: usize, expected_property: HeapProperty) {
-        assert_eq!(expected_property, heap.property);
-        assert_eq!(size, heap.elements.len());
-        assert_eq!(0, heap.size);
-        assert!(heap.is_empty());
-        for element in heap.elements.iter() {
-            assert_eq!(0, *element);
-        }
+impl<T, K: Ord, F> Compare<T> for ByKey<F>
+where
+    F: Fn(&T) -> K,
+{
+    fn compare(&self, a: &T, b: &T) -> Ordering {
+        self.property.compare(&(self.key)(a), &(self.key)(b))
     }
 
-    #[test]
-    fn default_constructor_with_zero() {
-        let size = 0;
-        let heap = create_heap(size);
-        assert_constructor(&heap, size, HeapProperty::Min);
+    fn property(&self) -> Option<HeapProperty> {
+        Some(self.property)
     }
+}
 
-    #[test]
-    fn default_constructor_with_one() {
-        let size = 1;
-        let heap = create_heap(size);
-        assert_constructor(&heap, size, HeapProperty::Min);
-    }
+/// Sorts `elements` in place: ascending for `Min` and descending for `Max`.
+pub fn heap_sort<T: Ord>(elements: &mut [T], property: HeapProperty) {
+    let reversed = |a: &T, b: &T| property.compare(b, a);
 
-    #[test]
-    fn default_constructor_with_five() {
-        let size = 5;
-        let heap = create_heap(size);
-        assert_constructor(&heap, size, HeapProperty::Min);
+    heapify(elements, &reversed);
+    for end in (1..elements.len()).rev() {
+        elements.swap(0, end);
+        sift_down(&mut elements[..end], 0, &reversed);
     }
+}
 
-    #[test]
-    fn constructor_with_zero_and_min() {
-        let size = 0;
-        let property = HeapProperty::Min;
-        let heap = create_heap_as(size, property);
-        assert_constructor(&heap, size, property);
-    }
+/// Rearranges `elements` into a binary heap in O(n) time. Together with the
+/// functions below, this works on plain slices and vectors, which is handy
+/// when the elements have to stay in caller-owned storage.
+pub fn heapify<T, C: Compare<T>>(elements: &mut [T], comparator: &C) {
+    d_ary_heapify::<_, _, 2>(elements, comparator);
+}
 
-    #[test]
-    fn default_constructor_with_one_and_min() {
-        let size = 1;
-        let property = HeapProperty::Min;
-        let heap = create_heap_as(size, property);
-        assert_constructor(&heap, size, property);
-    }
+/// Moves the element at `index` down until it is in heap order with its
+/// children. Does nothing if `index` is out of bounds.
+pub fn sift_down<T, C: Compare<T>>(elements: &mut [T], index: usize, comparator: &C) {
+    d_ary_sift_down::<_, _, 2>(elements, index, comparator);
+}
 
-    #[test]
-    fn default_constructor_with_five_and_min() {
-        let size = 5;
-        let property = HeapProperty::Min;
-        let heap = create_heap_as(size, property);
-        assert_constructor(&heap, size, property);
-    }
+/// Moves the element at `index` up until it is in heap order with its
+/// parent. Does nothing if `index` is out of bounds.
+pub fn sift_up<T, C: Compare<T>>(elements: &mut [T], index: usize, comparator: &C) {
+    d_ary_sift_up::<_, _, 2>(elements, index, comparator);
 }
 
-#[allow(dead_code)]
-mod test_util {
-    use super::Heap;
+/// Returns whether `elements` are in binary heap order under `comparator`.
+pub fn is_heap<T, C: Compare<T>>(elements: &[T], comparator: &C) -> bool {
+    d_ary_find_violation::<_, _, 2>(elements, comparator).is_none()
+}
 
-    pub fn assert_peek_on_empty_heap(heap: &Heap) {
-        let result = heap.peek();
+/// Adds `element` to the binary heap stored in `heap`.
+#[cfg(not(heap_in_rust_no_alloc))]
+pub fn heap_push<T, C: Compare<T>>(heap: &mut Vec<T>, element: T, comparator: &C) {
+    heap.push(element);
+    let index = heap.len() - 1;
+    sift_up(heap, index, comparator);
+}
 
-        assert!(result.is_err());
-        assert_eq!("Empty heap.".to_string(), result.unwrap_err());
+/// Removes the top of the binary heap stored in `heap`, or returns `None` if
+/// it is empty.
+#[cfg(not(heap_in_rust_no_alloc))]
+pub fn heap_pop<T, C: Compare<T>>(heap: &mut Vec<T>, comparator: &C) -> Option<T> {
+    if heap.is_empty() {
+        return None;
     }
 
-    pub fn assert_ok_result(result: Result<(), String>) {
-        assert!(result.is_ok());
-        assert_eq!((), result.unwrap());
-    }
+    let result = heap.swap_remove(0);
+    sift_down(heap, 0, comparator);
 
-    pub fn assert_value_result(result: Result<i32, String>, value: i32) {
-        assert!(result.is_ok());
-        assert_eq!(value, result.unwrap());
-    }
+    Some(result)
+}
 
-    pub fn assert_non_empty_heap(heap: &Heap, elements: Vec<i32>, size: usize) {
-        assert_eq!(elements.capacity(), heap.elements.len());
-        assert_eq!(elements, heap.elements);
-        assert_eq!(size, heap.size);
-        assert!(!heap.is_empty());
+fn get_parent_index<const D: usize>(index: usize) -> usize {
+    (index - 1) / D
+}
 
-        let peek_result = heap.peek();
+fn get_first_child_index<const D: usize>(index: usize) -> usize {
+    D * index + 1
+}
 
-        assert_value_result(peek_result, elements[0]);
-    }
+#[cfg(not(heap_in_rust_no_alloc))]
+fn get_left_child_index(index: usize) -> usize {
+    get_first_child_index::<2>(index)
+}
 
-    pub fn assert_empty_heap(heap: &Heap, elements: Vec<i32>) {
-        assert_eq!(elements.capacity(), heap.elements.len());
-        assert_eq!(elements, heap.elements);
-        assert_eq!(0, heap.size);
-        assert!(heap.is_empty());
+#[cfg(not(heap_in_rust_no_alloc))]
+fn get_right_child_index(index: usize) -> usize {
+    get_first_child_index::<2>(index) + 1
+}
 
-        assert_peek_on_empty_heap(&heap);
-    }
+fn is_root(index: usize) -> bool {
+    index == 0
 }
 
-#[cfg(test)]
-mod min_heap_tests {
-    use super::test_util;
-    use super::{Heap, HeapProperty};
+fn precedes<T, C: Compare<T>>(comparator: &C, element1: &T, element2: &T) -> bool {
+    comparator.compare(element1, element2) == Ordering::Less
+}
 
-    #[test]
-    fn peek_on_heap_with_size_as_zero() {
-        let heap = Heap::new_as(0, HeapProperty::Min);
-        test_util::assert_peek_on_empty_heap(&heap);
-    }
+/// A slot of `elements` whose element has been taken out while it is being
+/// sifted. Elements move into the hole one level at a time, and the taken
+/// element is written back into the hole's final position when the hole is
+/// dropped. That happens even if a comparator panics midway, so every element
+/// stays in `elements` exactly once.
+struct Hole<'a, T> {
+    elements: &'a mut [T],
+    element: ManuallyDrop<T>,
+    index: usize,
+}
 
-    #[test]
-    fn peek_on_heap_with_size_as_five() {
-        let heap = Heap::new_as(5, HeapProperty::Min);
-        test_util::assert_peek_on_empty_heap(&heap);
+impl<'a, T> Hole<'a, T> {
+    /// # Safety
+    ///
+    /// `index` must be within `elements`.
+    unsafe fn new(elements: &'a mut [T], index: usize) -> Self {
+        debug_assert!(index < elements.len());
+        let element = ptr::read(elements.get_unchecked(index));
+        Hole {
+            elements,
+            element: ManuallyDrop::new(element),
+            index,
+        }
     }
 
-    #[test]
-    fn add_six_elements_as_random() {
-        let mut heap = Heap::new_as(6, HeapProperty::Min);
+    fn index(&self) -> usize {
+        self.index
+    }
 
-        let result = heap.add(5);
+    fn element(&self) -> &T {
+        &self.element
+    }
 
-        test_util::assert_ok_result(result);
-        test_util::assert_non_empty_heap(&heap, vec![5, 0, 0, 0, 0, 0], 1);
+    /// # Safety
+    ///
+    /// `index` must be within `elements` and must not be the hole's index.
+    unsafe fn get(&self, index: usize) -> &T {
+        debug_assert!(index != self.index && index < self.elements.len());
+        self.elements.get_unchecked(index)
+    }
 
-        let result = heap.add(3);
+    /// Moves the element at `index` into the hole, which then moves to
+    /// `index`.
+    ///
+    /// # Safety
+    ///
+    /// `index` must be within `elements` and must not be the hole's index.
+    unsafe fn move_to(&mut self, index: usize) {
+        debug_assert!(index != self.index && index < self.elements.len());
+        let base = self.elements.as_mut_ptr();
+        ptr::copy_nonoverlapping(base.add(index), base.add(self.index), 1);
+        self.index = index;
+    }
+}
 
-        test_util::assert_ok_result(result);
-        test_util::assert_non_empty_heap(&heap, vec![3, 5, 0, 0, 0, 0], 2);
+impl<'a, T> Drop for Hole<'a, T> {
+    fn drop(&mut self) {
+        // SAFETY: the hole's index is always within `elements`, and its slot
+        // has been moved out of, so writing the taken element back is sound.
+        unsafe {
+            let index = self.index;
+            ptr::copy_nonoverlapping(&*self.element, self.elements.get_unchecked_mut(index), 1);
+        }
+    }
+}
 
-        let result = heap.add(9);
+fn d_ary_sift_down<T, C: Compare<T>, const D: usize>(
+    elements: &mut [T],
+    index: usize,
+    comparator: &C,
+) {
+    let size = elements.len();
+    if index >= size {
+        return;
+    }
+
+    // SAFETY: `index` is within `elements`, and the children visited below
+    // are within `elements` and always come after the hole.
+    unsafe {
+        let mut hole = Hole::new(elements, index);
+        while get_first_child_index::<D>(hole.index()) < size {
+            let first_child_index = get_first_child_index::<D>(hole.index());
+            let mut candidate_index = first_child_index;
+            for child_index in first_child_index + 1..size.min(first_child_index + D) {
+                if precedes(comparator, hole.get(child_index), hole.get(candidate_index)) {
+                    candidate_index = child_index;
+                }
+            }
 
-        test_util::assert_ok_result(result);
-        test_util::assert_non_empty_heap(&heap, vec![3, 5, 9, 0, 0, 0], 3);
+            if !precedes(comparator, hole.get(candidate_index), hole.element()) {
+                break;
+            }
 
-        let result = heap.add(8);
+            hole.move_to(candidate_index);
+        }
+    }
+}
 
-        test_util::assert_ok_result(result);
-        test_util::assert_non_empty_heap(&heap, vec![3, 5, 9, 8, 0, 0], 4);
+fn d_ary_sift_up<T, C: Compare<T>, const D: usize>(
+    elements: &mut [T],
+    index: usize,
+    comparator: &C,
+) {
+    if index >= elements.len() {
+        return;
+    }
+
+    // SAFETY: `index` is within `elements`, and the parents visited below
+    // always come before the hole.
+    unsafe {
+        let mut hole = Hole::new(elements, index);
+        while !is_root(hole.index()) {
+            let parent_index = get_parent_index::<D>(hole.index());
+            if !precedes(comparator, hole.element(), hole.get(parent_index)) {
+                break;
+            }
 
-        let result = heap.add(1);
+            hole.move_to(parent_index);
+        }
+    }
+}
 
-        test_util::assert_ok_result(result);
-        test_util::assert_non_empty_heap(&heap, vec![1, 3, 9, 8, 5, 0], 5);
+fn d_ary_find_violation<T, C: Compare<T>, const D: usize>(
+    elements: &[T],
+    comparator: &C,
+) -> Option<(usize, usize)> {
+    (1..elements.len())
+        .map(|child| (get_parent_index::<D>(child), child))
+        .find(|&(parent, child)| precedes(comparator, &elements[child], &elements[parent]))
+}
 
-        let result = heap.add(6);
+fn d_ary_heapify<T, C: Compare<T>, const D: usize>(elements: &mut [T], comparator: &C) {
+    if elements.len() < 2 {
+        return;
+    }
 
-        test_util::assert_ok_result(result);
-        test_util::assert_non_empty_heap(&heap, vec![1, 3, 6, 8, 5, 9], 6);
+    for index in (0..=get_parent_index::<D>(elements.len() - 1)).rev() {
+        d_ary_sift_down::<_, _, D>(elements, index, comparator);
+    }
+}
 
-        let result = heap.add(0);
+#[cfg(test)]
+mod heap_error_tests {
+    use super::HeapError;
 
-        assert!(result.is_err());
-        assert_eq!("Full heap.".to_string(), result.unwrap_err());
-        test_util::assert_non_empty_heap(&heap, vec![1, 3, 6, 8, 5, 9], 6);
+    #[test]
+    fn display_messages() {
+        assert_eq!("Empty heap.", HeapError::Empty.to_string());
+        assert_eq!("Full heap.", HeapError::Full { capacity: 6 }.to_string());
+        assert_eq!("Unknown handle.", HeapError::UnknownHandle.to_string());
+        assert_eq!(
+            "Mismatched heap properties.",
+            HeapError::PropertyMismatch.to_string()
+        );
     }
 
+    #[cfg(feature = "std")]
     #[test]
-    fn add_six_elements_as_sequential() {
-        let mut heap = Heap::new_as(6, HeapProperty::Min);
-
-        let result = heap.add(1);
-
-        test_util::assert_ok_result(result);
-        test_util::assert_non_empty_heap(&heap, vec![1, 0, 0, 0, 0, 0], 1);
-
-        let result = heap.add(2);
-
-        test_util::assert_ok_result(result);
-        test_util::assert_non_empty_heap(&heap, vec![1, 2, 0, 0, 0, 0], 2);
-
-        let result = heap.add(3);
-
-        test_util::assert_ok_result(result);
-        test_util::assert_non_empty_heap(&heap, vec![1, 2, 3, 0, 0, 0], 3);
-
-        let result = heap.add(4);
-
-        test_util::assert_ok_result(result);
-        test_util::assert_non_empty_heap(&heap, vec![1, 2, 3, 4, 0, 0], 4);
-
-        let result = heap.add(5);
+    fn propagates_with_question_mark() {
+        use super::Heap;
+        use std::error::Error;
+
+        fn pop_twice() -> Result<i32, Box<dyn Error>> {
+            let mut heap = Heap::new(1);
+            heap.add(1)?;
+            let first = heap.pop()?;
+            let second = heap.pop()?;
+            Ok(first + second)
+        }
 
-        test_util::assert_ok_result(result);
-        test_util::assert_non_empty_heap(&heap, vec![1, 2, 3, 4, 5, 0], 5);
+        let error = pop_twice().unwrap_err();
 
-        let result = heap.add(6);
+        assert_eq!(Some(&HeapError::Empty), error.downcast_ref::<HeapError>());
+    }
+}
 
-        test_util::assert_ok_result(result);
-        test_util::assert_non_empty_heap(&heap, vec![1, 2, 3, 4, 5, 6], 6);
+#[cfg(test)]
+mod hole_tests {
+    use super::{sift_down, sift_up, HeapProperty, Hole};
+    use std::cell::Cell;
+    use std::panic::{self, AssertUnwindSafe};
 
-        let result = heap.add(0);
+    #[test]
+    fn hole_writes_element_back_on_drop() {
+        let mut elements = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+
+        unsafe {
+            let mut hole = Hole::new(&mut elements, 0);
+            assert_eq!("a", hole.element());
+            assert_eq!("c", hole.get(2));
+            hole.move_to(2);
+            assert_eq!(2, hole.index());
+        }
 
-        assert!(result.is_err());
-        assert_eq!("Full heap.".to_string(), result.unwrap_err());
-        test_util::assert_non_empty_heap(&heap, vec![1, 2, 3, 4, 5, 6], 6);
+        assert_eq!(vec!["c", "b", "a"], elements);
     }
 
     #[test]
-    fn pop_from_six_elements_as_random() {
-        let mut heap = Heap::new_as(6, HeapProperty::Min);
-        heap.add(5).unwrap();
-        heap.add(3).unwrap();
-        heap.add(9).unwrap();
-        heap.add(8).unwrap();
-        heap.add(1).unwrap();
-        heap.add(6).unwrap();
-        test_util::assert_non_empty_heap(&heap, vec![1, 3, 6, 8, 5, 9], 6);
-
-        let result = heap.pop();
-
-        test_util::assert_value_result(result, 1);
-        test_util::assert_non_empty_heap(&heap, vec![3, 5, 6, 8, 9, 9], 5);
-
-        let result = heap.pop();
-
-        test_util::assert_value_result(result, 3);
-        test_util::assert_non_empty_heap(&heap, vec![5, 8, 6, 9, 9, 9], 4);
-
-        let result = heap.pop();
-
-        test_util::assert_value_result(result, 5);
-        test_util::assert_non_empty_heap(&heap, vec![6, 8, 9, 9, 9, 9], 3);
-
-        let result = heap.pop();
-
-        test_util::assert_value_result(result, 6);
-        test_util::assert_non_empty_heap(&heap, vec![8, 9, 9, 9, 9, 9], 2);
+    fn sift_down_and_up_move_one_element() {
+        let mut elements = vec![9, 1, 2, 3, 4, 5, 6];
+        sift_down(&mut elements, 0, &HeapProperty::Min);
 
-        let result = heap.pop();
+        assert_eq!(vec![1, 3, 2, 9, 4, 5, 6], elements);
 
-        test_util::assert_value_result(result, 8);
-        test_util::assert_non_empty_heap(&heap, vec![9, 9, 9, 9, 9, 9], 1);
+        elements.push(0);
+        sift_up(&mut elements, 7, &HeapProperty::Min);
 
-        let result = heap.pop();
-
-        test_util::assert_value_result(result, 9);
-        test_util::assert_empty_heap(&heap, vec![9, 9, 9, 9, 9, 9]);
-
-        let result = heap.pop();
-
-        assert!(result.is_err());
-        assert_eq!("Empty heap.".to_string(), result.unwrap_err());
-        test_util::assert_empty_heap(&heap, vec![9, 9, 9, 9, 9, 9]);
+        assert_eq!(vec![0, 1, 2, 3, 4, 5, 6, 9], elements);
     }
 
     #[test]
-    fn pop_from_six_elements_as_sequential() {
-        let mut heap = Heap::new_as(6, HeapProperty::Min);
-        heap.add(1).unwrap();
-        heap.add(2).unwrap();
-        heap.add(3).unwrap();
-        heap.add(4).unwrap();
-        heap.add(5).unwrap();
-        heap.add(6).unwrap();
-        test_util::assert_non_empty_heap(&heap, vec![1, 2, 3, 4, 5, 6], 6);
-
-        let result = heap.pop();
-
-        test_util::assert_value_result(result, 1);
-        test_util::assert_non_empty_heap(&heap, vec![2, 4, 3, 6, 5, 6], 5);
-
-        let result = heap.pop();
-
-        test_util::assert_value_result(result, 2);
-        test_util::assert_non_empty_heap(&heap, vec![3, 4, 5, 6, 5, 6], 4);
-
-        let result = heap.pop();
-
-        test_util::assert_value_result(result, 3);
-        test_util::assert_non_empty_heap(&heap, vec![4, 6, 5, 6, 5, 6], 3);
-
-        let result = heap.pop();
-
-        test_util::assert_value_result(result, 4);
-        test_util::assert_non_empty_heap(&heap, vec![5, 6, 5, 6, 5, 6], 2);
-
-        let result = heap.pop();
-
-        test_util::assert_value_result(result, 5);
-        test_util::assert_non_empty_heap(&heap, vec![6, 6, 5, 6, 5, 6], 1);
-
-        let result = heap.pop();
-
-        test_util::assert_value_result(result, 6);
-        test_util::assert_empty_heap(&heap, vec![6, 6, 5, 6, 5, 6]);
+    fn sift_keeps_elements_when_comparator_panics() {
+        let mut elements = vec![9, 1, 2, 3, 4, 5, 6];
+        let comparisons = Cell::new(0);
+        let comparator = |a: &i32, b: &i32| {
+            comparisons.set(comparisons.get() + 1);
+            if comparisons.get() == 3 {
+                panic!("comparator failure");
+            }
+            a.cmp(b)
+        };
 
-        let result = heap.pop();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            sift_down(&mut elements, 0, &comparator);
+        }));
 
         assert!(result.is_err());
-        assert_eq!("Empty heap.".to_string(), result.unwrap_err());
-        test_util::assert_empty_heap(&heap, vec![6, 6, 5, 6, 5, 6]);
+        let mut sorted = elements.clone();
+        sorted.sort_unstable();
+        assert_eq!(vec![1, 2, 3, 4, 5, 6, 9], sorted);
     }
 }
 
 #[cfg(test)]
-mod max_heap_tests {
-    use super::test_util;
-    use super::{Heap, HeapProperty};
-
-    #[test]
-    fn peek_on_heap_with_size_as_zero() {
-        let heap = Heap::new_as(0, HeapProperty::Max);
-        test_util::assert_peek_on_empty_heap(&heap);
-    }
-
-    #[test]
-    fn peek_on_heap_with_size_as_five() {
-        let heap = Heap::new_as(5, HeapProperty::Max);
-        test_util::assert_peek_on_empty_heap(&heap);
-    }
+mod heap_function_tests {
+    use super::{heapify, is_heap, sift_down, sift_up, HeapProperty};
 
     #[test]
-    fn add_six_elements_as_random() {
-        let mut heap = Heap::new_as(6, HeapProperty::Max);
-
-        let result = heap.add(5);
-
-        test_util::assert_ok_result(result);
-        test_util::assert_non_empty_heap(&heap, vec![5, 0, 0, 0, 0, 0], 1);
+    fn heapify_slice() {
+        let mut elements = [5, 3, 8, 1, 9, 2, 7];
 
-        let result = heap.add(3);
+        assert!(!is_heap(&elements, &HeapProperty::Min));
 
-        test_util::assert_ok_result(result);
-        test_util::assert_non_empty_heap(&heap, vec![5, 3, 0, 0, 0, 0], 2);
+        heapify(&mut elements, &HeapProperty::Min);
 
-        let result = heap.add(9);
-
-        test_util::assert_ok_result(result);
-        test_util::assert_non_empty_heap(&heap, vec![9, 3, 5, 0, 0, 0], 3);
-
-        let result = heap.add(8);
-
-        test_util::assert_ok_result(result);
-        test_util::assert_non_empty_heap(&heap, vec![9, 8, 5, 3, 0, 0], 4);
-
-        let result = heap.add(1);
-
-        test_util::assert_ok_result(result);
-        test_util::assert_non_empty_heap(&heap, vec![9, 8, 5, 3, 1, 0], 5);
-
-        let result = heap.add(6);
-
-        test_util::assert_ok_result(result);
-        test_util::assert_non_empty_heap(&heap, vec![9, 8, 6, 3, 1, 5], 6);
-
-        let result = heap.add(0);
-
-        assert!(result.is_err());
-        assert_eq!("Full heap.".to_string(), result.unwrap_err());
-        test_util::assert_non_empty_heap(&heap, vec![9, 8, 6, 3, 1, 5], 6);
+        assert!(is_heap(&elements, &HeapProperty::Min));
+        assert_eq!(1, elements[0]);
     }
 
     #[test]
-    fn add_six_elements_as_sequential() {
-        let mut heap = Heap::new_as(6, HeapProperty::Max);
-
-        let result = heap.add(1);
-
-        test_util::assert_ok_result(result);
-        test_util::assert_non_empty_heap(&heap, vec![1, 0, 0, 0, 0, 0], 1);
-
-        let result = heap.add(2);
+    fn is_heap_of_short_slices() {
+        let empty: [i32; 0] = [];
 
-        test_util::assert_ok_result(result);
-        test_util::assert_non_empty_heap(&heap, vec![2, 1, 0, 0, 0, 0], 2);
-
-        let result = heap.add(3);
-
-        test_util::assert_ok_result(result);
-        test_util::assert_non_empty_heap(&heap, vec![3, 1, 2, 0, 0, 0], 3);
-
-        let result = heap.add(4);
-
-        test_util::assert_ok_result(result);
-        test_util::assert_non_empty_heap(&heap, vec![4, 3, 2, 1, 0, 0], 4);
-
-        let result = heap.add(5);
-
-        test_util::assert_ok_result(result);
-        test_util::assert_non_empty_heap(&heap, vec![5, 4, 2, 1, 3, 0], 5);
-
-        let result = heap.add(6);
-
-        test_util::assert_ok_result(result);
-        test_util::assert_non_empty_heap(&heap, vec![6, 4, 5, 1, 3, 2], 6);
-
-        let result = heap.add(0);
-
-        assert!(result.is_err());
-        assert_eq!("Full heap.".to_string(), result.unwrap_err());
-        test_util::assert_non_empty_heap(&heap, vec![6, 4, 5, 1, 3, 2], 6);
+        assert!(is_heap(&empty, &HeapProperty::Max));
+        assert!(is_heap(&[1], &HeapProperty::Max));
+        assert!(!is_heap(&[1, 2], &HeapProperty::Max));
     }
 
     #[test]
-    fn pop_from_six_elements_as_random() {
-        let mut heap = Heap::new_as(6, HeapProperty::Max);
-        heap.add(5).unwrap();
-        heap.add(3).unwrap();
-        heap.add(9).unwrap();
-        heap.add(8).unwrap();
-        heap.add(1).unwrap();
-        heap.add(6).unwrap();
-        test_util::assert_non_empty_heap(&heap, vec![9, 8, 6, 3, 1, 5], 6);
-
-        let result = heap.pop();
+    fn sift_after_changing_element() {
+        let mut elements = vec![1, 3, 2, 7, 4, 5, 6];
 
-        test_util::assert_value_result(result, 9);
-        test_util::assert_non_empty_heap(&heap, vec![8, 5, 6, 3, 1, 5], 5);
+        elements[0] = 8;
+        sift_down(&mut elements, 0, &HeapProperty::Min);
 
-        let result = heap.pop();
+        assert!(is_heap(&elements, &HeapProperty::Min));
 
-        test_util::assert_value_result(result, 8);
-        test_util::assert_non_empty_heap(&heap, vec![6, 5, 1, 3, 1, 5], 4);
+        elements[6] = 0;
+        sift_up(&mut elements, 6, &HeapProperty::Min);
 
-        let result = heap.pop();
-
-        test_util::assert_value_result(result, 6);
-        test_util::assert_non_empty_heap(&heap, vec![5, 3, 1, 3, 1, 5], 3);
-
-        let result = heap.pop();
-
-        test_util::assert_value_result(result, 5);
-        test_util::assert_non_empty_heap(&heap, vec![3, 1, 1, 3, 1, 5], 2);
-
-        let result = heap.pop();
-
-        test_util::assert_value_result(result, 3);
-        test_util::assert_non_empty_heap(&heap, vec![1, 1, 1, 3, 1, 5], 1);
-
-        let result = heap.pop();
-
-        test_util::assert_value_result(result, 1);
-        test_util::assert_empty_heap(&heap, vec![1, 1, 1, 3, 1, 5]);
-
-        let result = heap.pop();
-
-        assert!(result.is_err());
-        assert_eq!("Empty heap.".to_string(), result.unwrap_err());
-        test_util::assert_empty_heap(&heap, vec![1, 1, 1, 3, 1, 5]);
+        assert!(is_heap(&elements, &HeapProperty::Min));
+        assert_eq!(0, elements[0]);
     }
 
     #[test]
-    fn pop_from_six_elements_as_sequential() {
-        let mut heap = Heap::new_as(6, HeapProperty::Max);
-        heap.add(1).unwrap();
-        heap.add(2).unwrap();
-        heap.add(3).unwrap();
-        heap.add(4).unwrap();
-        heap.add(5).unwrap();
-        heap.add(6).unwrap();
-        test_util::assert_non_empty_heap(&heap, vec![6, 4, 5, 1, 3, 2], 6);
-
-        let result = heap.pop();
-
-        test_util::assert_value_result(result, 6);
-        test_util::assert_non_empty_heap(&heap, vec![5, 4, 2, 1, 3, 2], 5);
-
-        let result = heap.pop();
+    fn sift_out_of_bounds_does_nothing() {
+        let mut elements = vec![2, 1];
 
-        test_util::assert_value_result(result, 5);
-        test_util::assert_non_empty_heap(&heap, vec![4, 3, 2, 1, 3, 2], 4);
+        sift_down(&mut elements, 2, &HeapProperty::Min);
+        sift_up(&mut elements, 2, &HeapProperty::Min);
 
-        let result = heap.pop();
-
-        test_util::assert_value_result(result, 4);
-        test_util::assert_non_empty_heap(&heap, vec![3, 1, 2, 1, 3, 2], 3);
-
-        let result = heap.pop();
-
-        test_util::assert_value_result(result, 3);
-        test_util::assert_non_empty_heap(&heap, vec![2, 1, 2, 1, 3, 2], 2);
-
-        let result = heap.pop();
+        assert_eq!(vec![2, 1], elements);
+    }
 
-        test_util::assert_value_result(result, 2);
-        test_util::assert_non_empty_heap(&heap, vec![1, 1, 2, 1, 3, 2], 1);
+    #[cfg(not(heap_in_rust_no_alloc))]
+    #[test]
+    fn push_and_pop_with_comparator() {
+        use super::{heap_pop, heap_push};
 
-        let result = heap.pop();
+        let by_length = |a: &&str, b: &&str| a.len().cmp(&b.len());
+        let mut elements = Vec::new();
+        for element in ["three", "one", "eleven", "four"] {
+            heap_push(&mut elements, element, &by_length);
+        }
 
-        test_util::assert_value_result(result, 1);
-        test_util::assert_empty_heap(&heap, vec![1, 1, 2, 1, 3, 2]);
+        assert_eq!(Some("one"), heap_pop(&mut elements, &by_length));
+        assert_eq!(Some("four"), heap_pop(&mut elements, &by_length));
+        assert_eq!(Some("three"), heap_pop(&mut elements, &by_length));
+        assert_eq!(Some("eleven"), heap_pop(&mut elements, &by_length));
+        assert_eq!(None, heap_pop(&mut elements, &by_length));
+    }
 
-        let result = heap.pop();
+    #[cfg(not(heap_in_rust_no_alloc))]
+    #[test]
+    fn heap_and_functions_agree() {
+        use super::{heap_pop, heap_push, Heap};
+
+        let mut elements = Vec::new();
+        let mut heap = Heap::with_capacity_as(0, HeapProperty::Max);
+        for element in [4, 9, 1, 7, 3, 8] {
+            heap_push(&mut elements, element, &HeapProperty::Max);
+            heap.add(element).unwrap();
+        }
 
-        assert!(result.is_err());
-        assert_eq!("Empty heap.".to_string(), result.unwrap_err());
-        test_util::assert_empty_heap(&heap, vec![1, 1, 2, 1, 3, 2]);
+        assert_eq!(heap.iter().copied().collect::<Vec<_>>(), elements);
+        while let Ok(element) = heap.pop() {
+            assert_eq!(Some(element), heap_pop(&mut elements, &HeapProperty::Max));
+        }
     }
 }
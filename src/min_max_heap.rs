@@ -0,0 +1,360 @@
+use alloc::vec::Vec;
+use core::fmt;
+use core::slice;
+
+use super::{
+    get_left_child_index, get_parent_index, get_right_child_index, is_root, precedes, HeapError,
+    HeapProperty,
+};
+
+/// A double-ended heap that gives access to both its smallest and its
+/// largest element. Levels of the tree alternate between min levels, starting
+/// with the root, and max levels: every element on a min level is smaller
+/// than its descendants and every element on a max level is larger.
+#[derive(Clone)]
+pub struct MinMaxHeap<T> {
+    elements: Vec<T>,
+    bound: Option<usize>,
+}
+
+impl<T: Ord> MinMaxHeap<T> {
+    pub fn new(size: usize) -> Self {
+        MinMaxHeap {
+            elements: Vec::with_capacity(size),
+            bound: Some(size),
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        MinMaxHeap {
+            elements: Vec::with_capacity(capacity),
+            bound: None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        Some(self.elements.len()) == self.bound
+    }
+
+    pub fn clear(&mut self) {
+        self.elements.clear();
+    }
+
+    /// Returns an iterator over the elements in the heap in arbitrary order.
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.elements.iter()
+    }
+
+    pub fn peek_min(&self) -> Result<&T, HeapError> {
+        self.validate_non_empty_heap()?;
+
+        Ok(&self.elements[0])
+    }
+
+    pub fn peek_max(&self) -> Result<&T, HeapError> {
+        self.validate_non_empty_heap()?;
+
+        Ok(&self.elements[self.get_max_index()])
+    }
+
+    pub fn pop_min(&mut self) -> Result<T, HeapError> {
+        self.validate_non_empty_heap()?;
+
+        Ok(self.remove_at(0))
+    }
+
+    pub fn pop_max(&mut self) -> Result<T, HeapError> {
+        self.validate_non_empty_heap()?;
+
+        let index = self.get_max_index();
+
+        Ok(self.remove_at(index))
+    }
+
+    pub fn add(&mut self, element: T) -> Result<(), HeapError> {
+        if let Some(capacity) = self.bound {
+            if self.elements.len() == capacity {
+                return Err(HeapError::Full { capacity });
+            }
+        }
+
+        self.elements.push(element);
+
+        self.recalculate_up(self.elements.len() - 1);
+
+        Ok(())
+    }
+
+    fn validate_non_empty_heap(&self) -> Result<(), HeapError> {
+        if self.elements.is_empty() {
+            return Err(HeapError::Empty);
+        }
+
+        Ok(())
+    }
+
+    fn get_max_index(&self) -> usize {
+        match self.elements.len() {
+            1 => 0,
+            2 => 1,
+            _ => {
+                if self.elements[2] > self.elements[1] {
+                    2
+                } else {
+                    1
+                }
+            }
+        }
+    }
+
+    fn remove_at(&mut self, index: usize) -> T {
+        let result = self.elements.swap_remove(index);
+
+        if index < self.elements.len() {
+            self.recalculate_down(index);
+        }
+
+        result
+    }
+
+    fn precedes_at(&self, property: HeapProperty, index1: usize, index2: usize) -> bool {
+        precedes(&property, &self.elements[index1], &self.elements[index2])
+    }
+
+    fn recalculate_up(&mut self, index: usize) {
+        if is_root(index) {
+            return;
+        }
+
+        let property = get_level_property(index);
+        let parent_index = get_parent_index::<2>(index);
+        if self.precedes_at(property, parent_index, index) {
+            self.elements.swap(parent_index, index);
+            self.recalculate_up_within_level(parent_index, get_level_property(parent_index));
+        } else {
+            self.recalculate_up_within_level(index, property);
+        }
+    }
+
+    fn recalculate_up_within_level(&mut self, mut index: usize, property: HeapProperty) {
+        while index > 2 {
+            let grandparent_index = get_parent_index::<2>(get_parent_index::<2>(index));
+            if !self.precedes_at(property, index, grandparent_index) {
+                break;
+            }
+
+            self.elements.swap(grandparent_index, index);
+            index = grandparent_index;
+        }
+    }
+
+    fn recalculate_down(&mut self, mut index: usize) {
+        let property = get_level_property(index);
+        while get_left_child_index(index) < self.elements.len() {
+            let candidate_index = self.get_best_descendant_index(index, property);
+            if !self.precedes_at(property, candidate_index, index) {
+                break;
+            }
+
+            self.elements.swap(candidate_index, index);
+            if get_parent_index::<2>(candidate_index) == index {
+                break;
+            }
+
+            let parent_index = get_parent_index::<2>(candidate_index);
+            if self.precedes_at(property, parent_index, candidate_index) {
+                self.elements.swap(parent_index, candidate_index);
+            }
+            index = candidate_index;
+        }
+    }
+
+    /// Returns the index of the child or grandchild of `index` that comes
+    /// first on a level with the given property.
+    fn get_best_descendant_index(&self, index: usize, property: HeapProperty) -> usize {
+        let left_child_index = get_left_child_index(index);
+        let right_child_index = get_right_child_index(index);
+        let grandchild_indices =
+            get_left_child_index(left_child_index)..=get_right_child_index(right_child_index);
+
+        let mut best_index = left_child_index;
+        for candidate_index in core::iter::once(right_child_index).chain(grandchild_indices) {
+            if candidate_index >= self.elements.len() {
+                break;
+            }
+            if self.precedes_at(property, candidate_index, best_index) {
+                best_index = candidate_index;
+            }
+        }
+
+        best_index
+    }
+}
+
+fn get_level_property(index: usize) -> HeapProperty {
+    let depth = usize::BITS - 1 - (index + 1).leading_zeros();
+    if depth % 2 == 0 {
+        HeapProperty::Min
+    } else {
+        HeapProperty::Max
+    }
+}
+
+impl<T: Ord> Default for MinMaxHeap<T> {
+    fn default() -> Self {
+        MinMaxHeap::with_capacity(0)
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for MinMaxHeap<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.elements.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod min_max_heap_tests {
+    use super::{get_level_property, MinMaxHeap};
+    use crate::heap::test_util::shuffled;
+    use crate::{HeapError, HeapProperty};
+
+    fn create_heap(elements: &[i32]) -> MinMaxHeap<i32> {
+        let mut heap = MinMaxHeap::with_capacity(elements.len());
+        for element in elements {
+            heap.add(*element).unwrap();
+        }
+        heap
+    }
+
+    fn assert_min_max_order(heap: &MinMaxHeap<i32>) {
+        for index in 1..heap.elements.len() {
+            let mut ancestor_index = index;
+            while ancestor_index > 0 {
+                ancestor_index = (ancestor_index - 1) / 2;
+                let ancestor = heap.elements[ancestor_index];
+                match get_level_property(ancestor_index) {
+                    HeapProperty::Min => assert!(ancestor <= heap.elements[index]),
+                    HeapProperty::Max => assert!(ancestor >= heap.elements[index]),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn level_properties() {
+        assert_eq!(HeapProperty::Min, get_level_property(0));
+        assert_eq!(HeapProperty::Max, get_level_property(1));
+        assert_eq!(HeapProperty::Max, get_level_property(2));
+        assert_eq!(HeapProperty::Min, get_level_property(3));
+        assert_eq!(HeapProperty::Min, get_level_property(6));
+        assert_eq!(HeapProperty::Max, get_level_property(7));
+    }
+
+    #[test]
+    fn peek_and_pop_on_empty_heap() {
+        let mut heap: MinMaxHeap<i32> = MinMaxHeap::new(3);
+
+        assert_eq!(HeapError::Empty, heap.peek_min().unwrap_err());
+        assert_eq!(HeapError::Empty, heap.peek_max().unwrap_err());
+        assert_eq!(HeapError::Empty, heap.pop_min().unwrap_err());
+        assert_eq!(HeapError::Empty, heap.pop_max().unwrap_err());
+    }
+
+    #[test]
+    fn add_six_elements_as_random() {
+        let heap = create_heap(&[5, 3, 9, 8, 1, 6]);
+
+        assert_min_max_order(&heap);
+        assert_eq!(1, *heap.peek_min().unwrap());
+        assert_eq!(9, *heap.peek_max().unwrap());
+    }
+
+    #[test]
+    fn add_to_bounded_heap() {
+        let mut heap = MinMaxHeap::new(2);
+        heap.add(2).unwrap();
+        heap.add(1).unwrap();
+
+        assert!(heap.is_full());
+        assert_eq!(HeapError::Full { capacity: 2 }, heap.add(3).unwrap_err());
+        assert_eq!(1, *heap.peek_min().unwrap());
+        assert_eq!(2, *heap.peek_max().unwrap());
+    }
+
+    #[test]
+    fn pop_min_in_order() {
+        let mut heap = create_heap(&shuffled(100));
+
+        for expected in 0..100 {
+            assert_eq!(expected, heap.pop_min().unwrap());
+            assert_min_max_order(&heap);
+        }
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn pop_max_in_order() {
+        let mut heap = create_heap(&shuffled(100));
+
+        for expected in (0..100).rev() {
+            assert_eq!(expected, heap.pop_max().unwrap());
+            assert_min_max_order(&heap);
+        }
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn pop_from_both_ends() {
+        let mut heap = create_heap(&[4, 1, 4, 7, 1, 9, 3, 3]);
+
+        assert_eq!(1, heap.pop_min().unwrap());
+        assert_eq!(9, heap.pop_max().unwrap());
+        assert_eq!(7, heap.pop_max().unwrap());
+        assert_eq!(1, heap.pop_min().unwrap());
+        assert_eq!(3, heap.pop_min().unwrap());
+        assert_eq!(4, heap.pop_max().unwrap());
+        assert_min_max_order(&heap);
+        assert_eq!(2, heap.len());
+        assert_eq!(3, *heap.peek_min().unwrap());
+        assert_eq!(4, *heap.peek_max().unwrap());
+    }
+
+    #[test]
+    fn evict_worst_when_full() {
+        let mut heap = MinMaxHeap::new(3);
+        for element in shuffled(20) {
+            if heap.is_full() {
+                if element <= *heap.peek_min().unwrap() {
+                    continue;
+                }
+                heap.pop_min().unwrap();
+            }
+            heap.add(element).unwrap();
+        }
+
+        assert_eq!(19, heap.pop_max().unwrap());
+        assert_eq!(18, heap.pop_max().unwrap());
+        assert_eq!(17, heap.pop_max().unwrap());
+    }
+
+    #[test]
+    fn clear_and_debug() {
+        let mut heap = create_heap(&[2, 1]);
+
+        assert_eq!("[1, 2]", format!("{:?}", heap));
+        assert_eq!(2, heap.iter().count());
+
+        heap.clear();
+
+        assert!(heap.is_empty());
+        assert_eq!("[]", format!("{:?}", MinMaxHeap::<i32>::default()));
+    }
+}
@@ -1,43 +1,46 @@
+#![cfg(not(heap_in_rust_no_alloc))]
+
 #[allow(dead_code)]
 mod test_util {
-    use heap_in_rust::Heap;
+    use heap_in_rust::{Heap, HeapError};
 
-    pub fn assert_peek_on_empty_heap(heap: &Heap) {
+    pub fn assert_peek_on_empty_heap(heap: &Heap<i32>) {
         let result = heap.peek();
 
         assert!(result.is_err());
-        assert_eq!("Empty heap.".to_string(), result.unwrap_err());
+        assert_eq!(HeapError::Empty, result.unwrap_err());
     }
 
-    pub fn assert_ok_result(result: Result<(), String>) {
+    pub fn assert_ok_result(result: Result<(), HeapError>) {
         assert!(result.is_ok());
         assert_eq!((), result.unwrap());
     }
 
-    pub fn assert_value_result(result: Result<i32, String>, value: i32) {
+    pub fn assert_value_result(result: Result<i32, HeapError>, value: i32) {
         assert!(result.is_ok());
         assert_eq!(value, result.unwrap());
     }
 
-    pub fn assert_non_empty_heap(heap: &Heap, top_element: i32) {
+    pub fn assert_non_empty_heap(heap: &Heap<i32>, top_element: i32) {
         assert!(!heap.is_empty());
 
-        let peek_result = heap.peek();
+        let peek_result = heap.peek().copied();
 
         assert_value_result(peek_result, top_element);
     }
 
-    pub fn assert_empty_heap(heap: &Heap) {
+    pub fn assert_empty_heap(heap: &Heap<i32>) {
         assert!(heap.is_empty());
+        assert_eq!(0, heap.len());
 
-        assert_peek_on_empty_heap(&heap);
+        assert_peek_on_empty_heap(heap);
     }
 }
 
 #[cfg(test)]
 mod min_heap_tests {
     use super::test_util;
-    use heap_in_rust::{Heap, HeapProperty};
+    use heap_in_rust::{Heap, HeapError, HeapProperty};
 
     #[test]
     fn add_six_elements_as_random() {
@@ -76,7 +79,7 @@ mod min_heap_tests {
         let result = heap.add(0);
 
         assert!(result.is_err());
-        assert_eq!("Full heap.".to_string(), result.unwrap_err());
+        assert_eq!(HeapError::Full { capacity: 6 }, result.unwrap_err());
         test_util::assert_non_empty_heap(&heap, 1);
     }
 
@@ -117,7 +120,7 @@ mod min_heap_tests {
         let result = heap.add(0);
 
         assert!(result.is_err());
-        assert_eq!("Full heap.".to_string(), result.unwrap_err());
+        assert_eq!(HeapError::Full { capacity: 6 }, result.unwrap_err());
         test_util::assert_non_empty_heap(&heap, 1);
     }
 
@@ -165,7 +168,7 @@ mod min_heap_tests {
         let result = heap.pop();
 
         assert!(result.is_err());
-        assert_eq!("Empty heap.".to_string(), result.unwrap_err());
+        assert_eq!(HeapError::Empty, result.unwrap_err());
         test_util::assert_empty_heap(&heap);
     }
 
@@ -213,7 +216,7 @@ mod min_heap_tests {
         let result = heap.pop();
 
         assert!(result.is_err());
-        assert_eq!("Empty heap.".to_string(), result.unwrap_err());
+        assert_eq!(HeapError::Empty, result.unwrap_err());
         test_util::assert_empty_heap(&heap);
     }
 }
@@ -221,7 +224,7 @@ mod min_heap_tests {
 #[cfg(test)]
 mod max_heap_tests {
     use super::test_util;
-    use heap_in_rust::{Heap, HeapProperty};
+    use heap_in_rust::{Heap, HeapError, HeapProperty};
 
     #[test]
     fn add_six_elements_as_random() {
@@ -260,7 +263,7 @@ mod max_heap_tests {
         let result = heap.add(0);
 
         assert!(result.is_err());
-        assert_eq!("Full heap.".to_string(), result.unwrap_err());
+        assert_eq!(HeapError::Full { capacity: 6 }, result.unwrap_err());
         test_util::assert_non_empty_heap(&heap, 9);
     }
 
@@ -301,7 +304,7 @@ mod max_heap_tests {
         let result = heap.add(0);
 
         assert!(result.is_err());
-        assert_eq!("Full heap.".to_string(), result.unwrap_err());
+        assert_eq!(HeapError::Full { capacity: 6 }, result.unwrap_err());
         test_util::assert_non_empty_heap(&heap, 6);
     }
 
@@ -349,7 +352,7 @@ mod max_heap_tests {
         let result = heap.pop();
 
         assert!(result.is_err());
-        assert_eq!("Empty heap.".to_string(), result.unwrap_err());
+        assert_eq!(HeapError::Empty, result.unwrap_err());
         test_util::assert_empty_heap(&heap);
     }
 
@@ -397,7 +400,214 @@ mod max_heap_tests {
         let result = heap.pop();
 
         assert!(result.is_err());
-        assert_eq!("Empty heap.".to_string(), result.unwrap_err());
+        assert_eq!(HeapError::Empty, result.unwrap_err());
         test_util::assert_empty_heap(&heap);
     }
 }
+
+#[cfg(test)]
+mod growable_heap_tests {
+    use super::test_util;
+    use heap_in_rust::{Heap, HeapProperty};
+
+    #[test]
+    fn add_and_pop_without_size_limit() {
+        let mut heap = Heap::with_capacity_as(2, HeapProperty::Min);
+        test_util::assert_empty_heap(&heap);
+
+        for element in [5, 3, 9, 8, 1, 6] {
+            test_util::assert_ok_result(heap.add(element));
+        }
+
+        test_util::assert_non_empty_heap(&heap, 1);
+
+        for element in [1, 3, 5, 6, 8, 9] {
+            test_util::assert_value_result(heap.pop(), element);
+        }
+
+        test_util::assert_empty_heap(&heap);
+    }
+}
+
+#[cfg(test)]
+mod heapify_tests {
+    use super::test_util;
+    use heap_in_rust::{Heap, HeapProperty};
+
+    #[test]
+    fn pop_from_vec_as_max() {
+        let mut heap = Heap::from_vec(vec![5, 3, 9, 8, 1, 6], HeapProperty::Max);
+        test_util::assert_non_empty_heap(&heap, 9);
+
+        for element in [9, 8, 6, 5, 3, 1] {
+            test_util::assert_value_result(heap.pop(), element);
+        }
+
+        test_util::assert_empty_heap(&heap);
+    }
+}
+
+#[cfg(test)]
+mod indexed_heap_tests {
+    use heap_in_rust::{Handle, IndexedHeap};
+
+    fn shortest_distances(edges: &[Vec<(usize, u32)>], source: usize) -> Vec<Option<u32>> {
+        let mut distances = vec![None; edges.len()];
+        let mut heap = IndexedHeap::with_capacity_and_comparator(
+            edges.len(),
+            |a: &(u32, usize), b: &(u32, usize)| a.0.cmp(&b.0),
+        );
+        let mut handles: Vec<Option<Handle>> = vec![None; edges.len()];
+        handles[source] = Some(heap.add((0, source)).unwrap());
+
+        while let Ok((_, (distance, node))) = heap.pop() {
+            distances[node] = Some(distance);
+            for &(next, weight) in &edges[node] {
+                if distances[next].is_some() {
+                    continue;
+                }
+                let candidate = (distance + weight, next);
+                match handles[next] {
+                    Some(handle) if heap.contains(handle) => {
+                        if candidate.0 < heap.get(handle).unwrap().0 {
+                            heap.change_priority(handle, candidate).unwrap();
+                        }
+                    }
+                    _ => handles[next] = Some(heap.add(candidate).unwrap()),
+                }
+            }
+        }
+
+        distances
+    }
+
+    #[test]
+    fn dijkstra_with_decrease_key() {
+        let edges = vec![
+            vec![(1, 7), (2, 9), (5, 14)],
+            vec![(0, 7), (2, 10), (3, 15)],
+            vec![(0, 9), (1, 10), (3, 11), (5, 2)],
+            vec![(1, 15), (2, 11), (4, 6)],
+            vec![(3, 6), (5, 9)],
+            vec![(0, 14), (2, 2), (4, 9)],
+            vec![],
+        ];
+
+        assert_eq!(
+            vec![
+                Some(0),
+                Some(7),
+                Some(9),
+                Some(20),
+                Some(20),
+                Some(11),
+                None
+            ],
+            shortest_distances(&edges, 0)
+        );
+    }
+}
+
+#[cfg(test)]
+mod typed_heap_tests {
+    use heap_in_rust::{Heap, HeapError, MaxHeap, MinHeap, TypedHeap};
+
+    fn top_three(heap: &mut MinHeap<u32>, element: u32) {
+        if heap.is_full() {
+            heap.push_pop(element);
+        } else {
+            heap.add(element).unwrap();
+        }
+    }
+
+    #[test]
+    fn keep_largest_elements_in_min_heap() {
+        let mut heap = MinHeap::bounded(3);
+        for element in [7, 2, 9, 4, 8, 1] {
+            top_three(&mut heap, element);
+        }
+
+        assert_eq!(vec![7, 8, 9], heap.into_sorted_vec());
+    }
+
+    #[test]
+    fn pop_from_max_heap() {
+        let mut heap: MaxHeap<&str> = MaxHeap::growable(0);
+        heap.add("b").unwrap();
+        heap.add("c").unwrap();
+        heap.add("a").unwrap();
+
+        assert_eq!(Ok("c"), heap.pop());
+        assert_eq!(Ok("b"), heap.pop());
+        assert_eq!(Ok("a"), heap.pop());
+        assert_eq!(Err(HeapError::Empty), heap.pop());
+    }
+
+    #[test]
+    fn typed_constructors_leave_runtime_heaps_alone() {
+        let mut typed_heap = MaxHeap::new(2);
+        let mut runtime_heap = Heap::new(2);
+        for element in [4, 6] {
+            typed_heap.add(element).unwrap();
+            runtime_heap.add(element).unwrap();
+        }
+
+        assert_eq!(Ok(&6), typed_heap.peek());
+        assert_eq!(Ok(&4), runtime_heap.peek());
+        assert_eq!(vec![4, 6], runtime_heap.into_sorted_vec());
+    }
+}
+
+#[cfg(test)]
+mod heap_function_tests {
+    use heap_in_rust::{heap_pop, heap_push, is_heap, HeapProperty};
+
+    #[test]
+    fn schedule_tasks_in_vec() {
+        let by_priority = |a: &(u8, &str), b: &(u8, &str)| b.0.cmp(&a.0);
+        let mut tasks = Vec::new();
+        heap_push(&mut tasks, (2, "write"), &by_priority);
+        heap_push(&mut tasks, (9, "deploy"), &by_priority);
+        heap_push(&mut tasks, (5, "review"), &by_priority);
+
+        assert!(is_heap(&tasks, &by_priority));
+        assert_eq!(Some((9, "deploy")), heap_pop(&mut tasks, &by_priority));
+        assert_eq!(Some((5, "review")), heap_pop(&mut tasks, &by_priority));
+        assert_eq!(Some((2, "write")), heap_pop(&mut tasks, &by_priority));
+        assert_eq!(None, heap_pop(&mut tasks, &HeapProperty::Min));
+    }
+}
+
+#[cfg(test)]
+mod key_heap_tests {
+    use heap_in_rust::{Heap, HeapProperty};
+
+    struct Job {
+        name: &'static str,
+        deadline: u32,
+    }
+
+    #[test]
+    fn schedule_jobs_by_deadline() {
+        let mut heap = Heap::<Job, _>::by_key(|job| job.deadline, HeapProperty::Min);
+        heap.add(Job {
+            name: "deploy",
+            deadline: 50,
+        })
+        .unwrap();
+        heap.add(Job {
+            name: "test",
+            deadline: 10,
+        })
+        .unwrap();
+        heap.add(Job {
+            name: "build",
+            deadline: 30,
+        })
+        .unwrap();
+
+        let names: Vec<&str> = heap.drain_sorted().map(|job| job.name).collect();
+
+        assert_eq!(vec!["test", "build", "deploy"], names);
+    }
+}